@@ -1,3 +1,11 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type ApiErrorInner = { "type": "Database", } | { "type": "DatabaseConnectionPool", } | { "type": "InvalidQuery", } | { "type": "UrlParseError", } | { "type": "SessionError", } | { "type": "AxumError", } | { "type": "ApiFailed", } | { "type": "ApiReturnedError", } | { "type": "InvalidJson", } | { "type": "InvalidPath", } | { "type": "InvalidOauth", } | { "type": "UnexpectedResponse", } | { "type": "Rejected", } | { "type": "MapNotFound", } | { "type": "MultipartError", } | { "type": "InvalidMultipart", } | { "type": "MissingFromMultipart", } | { "type": "NotValidated" } | { "type": "InvalidGbx", } | { "type": "NotAMap" } | { "type": "AlreadyUploaded", map_id: number, } | { "type": "NotYourMapUpload" } | { "type": "InvalidThumbnail", } | { "type": "StdIo", } | { "type": "NoSuchTag", tag: string, } | { "type": "TooManyTags", max: number, } | { "type": "NotBase64", } | { "type": "NotUtf8", } | { "type": "NotUuid", } | { "type": "NotFound", } | { "type": "Time", } | { "type": "TimeRange", } | { "type": "Tera", } | { "type": "Json", } | { "type": "LastModifiedTimeTooLarge" } | { "type": "InvalidMap", error: string, } | { "type": "MissingAuthor", map_id: number, } | { "type": "MissingUploader", map_id: number, } | { "type": "NotYourMapManage" } | { "type": "NotYourMapGrant" } | { "type": "CannotUsurpAuthor" } | { "type": "CannotModifySelfPermissions" };
+export type ApiErrorInner = { "type": "Database", } | { "type": "DatabaseConnectionPool", } | { "type": "InvalidQuery", } | { "type": "UrlParseError", } | { "type": "SessionError", } | { "type": "AxumError", } | { "type": "ApiFailed", } | { "type": "ApiReturnedError", } | { "type": "InvalidJson", } | { "type": "InvalidPath", } | { "type": "InvalidOauth", } | { "type": "UnexpectedResponse", } | { "type": "Rejected", } | { "type": "MapNotFound", } | { "type": "MultipartError", } | { "type": "InvalidMultipart", } | { "type": "MissingFromMultipart", } | { "type": "NotValidated" } | { "type": "InvalidGbx", 
+/**
+ * Where in the file parsing failed, so it can be reproduced from the file
+ */
+location: string | null, } | { "type": "NotAMap" } | { "type": "AlreadyUploaded", map_id: number, } | { "type": "NotYourMapUpload" } | { "type": "InvalidThumbnail", } | { "type": "StdIo", } | { "type": "Task", } | { "type": "NoSuchTag", tag: string, } | { "type": "TooManyTags", max: number, } | { "type": "NotBase64", } | { "type": "NotUtf8", } | { "type": "NotUuid", } | { "type": "NotFound", } | { "type": "Time", } | { "type": "TimeRange", } | { "type": "Tera", } | { "type": "Json", } | { "type": "LastModifiedTimeTooLarge" } | { "type": "InvalidMap", error: string, } | { "type": "XmlMismatch", 
+/**
+ * Each field that differs, with its value in the XML header and in the map
+ */
+mismatches: Array<string>, } | { "type": "MissingAuthor", map_id: number, } | { "type": "MissingUploader", map_id: number, } | { "type": "NotYourMapManage" } | { "type": "NotYourMapGrant" } | { "type": "CannotUsurpAuthor" } | { "type": "CannotModifySelfPermissions" };
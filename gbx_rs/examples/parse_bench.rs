@@ -0,0 +1,42 @@
+use std::time::{Duration, Instant};
+
+/// Times parsing each map given on the command line, e.g.
+/// `cargo run --release --example parse_bench -- gbx_rs/examples/*.Map.Gbx`
+fn main() -> Result<(), &'static str> {
+    let filenames = std::env::args().skip(1).collect::<Vec<_>>();
+    if filenames.is_empty() {
+        return Err("expected map filenames");
+    }
+    let iterations = std::env::var("ITERATIONS")
+        .ok()
+        .and_then(|iterations| iterations.parse().ok())
+        .unwrap_or(50u32);
+
+    for filename in filenames {
+        let data = std::fs::read(&filename).map_err(|_| "couldn't read file")?;
+        // decompressed once up front, so only parsing is timed
+        let node = gbx_rs::Node::read_from(&data).map_err(|_| "couldn't read node")?;
+        node.parse().map_err(|_| "couldn't parse node")?;
+
+        let mut total = Duration::ZERO;
+        let mut fastest = Duration::MAX;
+        for _ in 0..iterations {
+            let start = Instant::now();
+            let parsed = node.parse().map_err(|_| "couldn't parse node")?;
+            let elapsed = start.elapsed();
+            drop(parsed);
+            total += elapsed;
+            fastest = fastest.min(elapsed);
+        }
+
+        println!("{}", filename);
+        println!(
+            "    {} iterations, mean {:.3} ms, fastest {:.3} ms",
+            iterations,
+            total.as_secs_f64() * 1000.0 / iterations as f64,
+            fastest.as_secs_f64() * 1000.0
+        );
+    }
+
+    Ok(())
+}
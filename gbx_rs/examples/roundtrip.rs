@@ -0,0 +1,54 @@
+use std::io::Read;
+
+fn main() -> Result<(), &'static str> {
+    tracing_subscriber::fmt::init();
+
+    let filenames = std::env::args().skip(1).collect::<Vec<_>>();
+    if filenames.is_empty() {
+        return Err("expected map filenames");
+    }
+
+    for filename in filenames {
+        println!("{}", filename);
+        let mut file = std::fs::File::open(&filename).map_err(|_| "couldn't open file")?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)
+            .map_err(|_| "couldn't read file")?;
+
+        let node = gbx_rs::Node::read_from(&data).map_err(|_| "couldn't read node")?;
+        let Ok(gbx_rs::parse::CGame::CtnChallenge(map)) = node.parse() else {
+            return Err("not a map");
+        };
+
+        let written = node.write_challenge(&map).map_err(|err| {
+            println!("{}", err);
+            "couldn't write map"
+        })?;
+        if written != data {
+            return Err("unchanged map did not round-trip");
+        }
+        println!("    round-trip ok ({} bytes)", written.len());
+
+        let mut edited = map.clone();
+        edited.map_name = Some("Round-trip test".into());
+        edited.password = Some("".into());
+        let written = node.write_challenge(&edited).map_err(|err| {
+            println!("{}", err);
+            "couldn't write edited map"
+        })?;
+
+        let edited_node =
+            gbx_rs::Node::read_from(&written).map_err(|_| "couldn't read edited map")?;
+        let Ok(gbx_rs::parse::CGame::CtnChallenge(edited_map)) = edited_node.parse() else {
+            return Err("couldn't parse edited map");
+        };
+        if edited_map.map_name.as_deref() != Some("Round-trip test")
+            || edited_map.blocks.len() != map.blocks.len()
+        {
+            return Err("edited map did not parse back");
+        }
+        println!("    edit ok ({} bytes)", written.len());
+    }
+
+    Ok(())
+}
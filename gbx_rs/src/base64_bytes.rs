@@ -0,0 +1,45 @@
+//! Serializes binary fields, like thumbnails and embedded files, as base64 strings, which are far
+//! smaller in JSON than arrays of numbers
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::{Deserialize, Deserializer, Serializer};
+use std::borrow::Cow;
+
+pub(crate) fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&BASE64_STANDARD.encode(data))
+}
+
+pub(crate) fn deserialize<'de, 'node, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Cow<'node, [u8]>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    BASE64_STANDARD
+        .decode(encoded)
+        .map(Cow::Owned)
+        .map_err(serde::de::Error::custom)
+}
+
+/// For `Option` fields, where `None` is `null`
+pub(crate) mod option {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        data: &Option<Cow<'_, [u8]>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match data {
+            Some(data) => super::serialize(data, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, 'node, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Cow<'node, [u8]>>, D::Error> {
+        let encoded = Option::<String>::deserialize(deserializer)?;
+        encoded
+            .map(|encoded| BASE64_STANDARD.decode(encoded).map(Cow::Owned))
+            .transpose()
+            .map_err(serde::de::Error::custom)
+    }
+}
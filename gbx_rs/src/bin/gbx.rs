@@ -0,0 +1,395 @@
+//! Command-line tool for inspecting and checking GBX files
+
+use clap::{Parser, Subcommand};
+use gbx_rs::{parse::CGame, GbxError, ItemIcon, Node, ParseReport};
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+#[derive(Parser)]
+#[command(name = "gbx", version, about = "Inspect and check GBX files")]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the header and the list of header and body chunks
+    Inspect { file: PathBuf },
+
+    /// Print the parsed file as JSON
+    Dump {
+        file: PathBuf,
+        /// Only parse header chunks, without decompressing the body
+        #[arg(long)]
+        header_only: bool,
+        #[arg(long)]
+        pretty: bool,
+    },
+
+    /// Extract a map's thumbnail JPEG, or the WebP icon of an item or macroblock
+    Thumbnail {
+        file: PathBuf,
+        /// Where to write the JPEG; standard output if not given
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Fully parse every GBX file under the given paths. Exits with status 1 if any file fails
+    /// to parse, or is a map whose XML header disagrees with its data.
+    Validate {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Print one JSON object per file instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print the UID of each map, followed by its path. Directories are searched for
+    /// `.Map.Gbx` files.
+    Uid {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+}
+
+fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args = Args::parse();
+    let mut out = io::stdout().lock();
+    let result = match args.command {
+        Command::Inspect { file } => inspect(&mut out, &file),
+        Command::Dump {
+            file,
+            header_only,
+            pretty,
+        } => dump(&mut out, &file, header_only, pretty),
+        Command::Thumbnail { file, output } => thumbnail(&mut out, &file, output.as_deref()),
+        Command::Validate { paths, json } => validate(&mut out, &paths, json),
+        Command::Uid { paths } => uid(&mut out, &paths),
+    };
+
+    match result.and_then(|code| out.flush().map(|()| code)) {
+        Ok(code) => code,
+        // e.g. piped into `head`
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn read(path: &Path) -> io::Result<Vec<u8>> {
+    std::fs::read(path).map_err(|err| io::Error::new(err.kind(), file_error(path, err)))
+}
+
+fn file_error(path: &Path, err: impl std::fmt::Display) -> String {
+    format!("{}: {}", path.display(), err)
+}
+
+fn gbx_error(path: &Path) -> impl Fn(GbxError) -> io::Error + '_ {
+    move |err| io::Error::other(file_error(path, err))
+}
+
+fn inspect(out: &mut impl Write, path: &Path) -> io::Result<ExitCode> {
+    let data = read(path)?;
+    let node = Node::read_from(&data).map_err(gbx_error(path))?;
+    let header = node.header();
+
+    writeln!(out, "version {}", header.version)?;
+    writeln!(out, "class {:08x}", header.class_id)?;
+    writeln!(
+        out,
+        "byte format {:?}, body {:?}, reference table {:?}",
+        header.byte_format, header.body_compression, header.ref_table_compression
+    )?;
+    writeln!(
+        out,
+        "{} nodes, {} external nodes",
+        header.num_nodes, header.num_external_nodes
+    )?;
+    if !header.external_refs.is_empty() {
+        writeln!(
+            out,
+            "external nodes, from {} folders up:",
+            header.ancestor_level
+        )?;
+        for external_ref in header.external_refs.iter() {
+            match (external_ref.path(), external_ref.resource_index) {
+                (Some(path), _) => writeln!(out, "    {}, {}", external_ref.node_index, path)?,
+                (None, resource_index) => writeln!(
+                    out,
+                    "    {}, resource {}",
+                    external_ref.node_index,
+                    resource_index.unwrap_or_default()
+                )?,
+            }
+        }
+    }
+
+    writeln!(out, "header chunks:")?;
+    for chunk in header.chunks.iter() {
+        writeln!(
+            out,
+            "    {:08x}, {} bytes{}",
+            chunk.full_id,
+            chunk.size,
+            if chunk.heavy { ", heavy" } else { "" }
+        )?;
+    }
+
+    writeln!(out, "body chunks:")?;
+    for chunk in node.body_chunks().map_err(gbx_error(path))? {
+        writeln!(
+            out,
+            "    {:08x}, {} bytes{}",
+            chunk.id,
+            chunk.size,
+            if chunk.skippable { ", skippable" } else { "" }
+        )?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn dump(
+    out: &mut impl Write,
+    path: &Path,
+    header_only: bool,
+    pretty: bool,
+) -> io::Result<ExitCode> {
+    let data = read(path)?;
+    let parsed = if header_only {
+        Node::read_header(&data).and_then(|node| node.parse_header().map(CGame::into_owned))
+    } else {
+        Node::read_from(&data).and_then(|node| node.parse().map(CGame::into_owned))
+    }
+    .map_err(gbx_error(path))?;
+
+    if pretty {
+        serde_json::to_writer_pretty(&mut *out, &parsed)?;
+    } else {
+        serde_json::to_writer(&mut *out, &parsed)?;
+    }
+    writeln!(out)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn thumbnail(out: &mut impl Write, path: &Path, output: Option<&Path>) -> io::Result<ExitCode> {
+    let data = read(path)?;
+    let node = Node::read_header(&data).map_err(gbx_error(path))?;
+    let (thumbnail, missing) = match node.parse_header() {
+        Ok(CGame::CtnChallenge(map)) => (map.thumbnail_data, "map has no thumbnail"),
+        Ok(CGame::ItemModel(item)) => (webp_icon(item.icon), "item has no WebP icon"),
+        Ok(CGame::CtnMacroBlockInfo(macroblock)) => {
+            (webp_icon(macroblock.icon), "macroblock has no WebP icon")
+        }
+        _ => {
+            return Err(io::Error::other(file_error(
+                path,
+                "not a map, item or macroblock",
+            )))
+        }
+    };
+    let Some(thumbnail) = thumbnail else {
+        return Err(io::Error::other(file_error(path, missing)));
+    };
+
+    match output {
+        Some(output) => std::fs::write(output, &thumbnail)
+            .map_err(|err| io::Error::new(err.kind(), file_error(output, err)))?,
+        None => out.write_all(&thumbnail)?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Older icons are raw pixels, which would need encoding first
+fn webp_icon(icon: Option<ItemIcon<'_>>) -> Option<Cow<'_, [u8]>> {
+    icon.filter(|icon| icon.webp).map(|icon| icon.data)
+}
+
+#[derive(serde::Serialize)]
+struct Validation {
+    path: PathBuf,
+    class_id: Option<u32>,
+    /// Why the file couldn't be parsed
+    error: Option<String>,
+    /// Values that differ between a map's XML header and its data
+    mismatches: Vec<String>,
+    report: Option<ParseReport>,
+}
+
+fn validate(out: &mut impl Write, paths: &[PathBuf], json: bool) -> io::Result<ExitCode> {
+    let files = expand_paths(paths, |_| true);
+
+    let mut num_invalid = 0;
+    for path in files.iter() {
+        let validation = validate_file(path);
+        let valid = validation.error.is_none() && validation.mismatches.is_empty();
+        if !valid {
+            num_invalid += 1;
+        }
+
+        if json {
+            serde_json::to_writer(&mut *out, &validation)?;
+            writeln!(out)?;
+        } else if valid {
+            let skipped = validation.report.as_ref().map_or(0, |report| {
+                report.skipped_chunks.len() + report.force_skipped_chunks.len()
+            });
+            writeln!(
+                out,
+                "ok      {} ({} chunks skipped)",
+                path.display(),
+                skipped
+            )?;
+        } else {
+            writeln!(out, "invalid {}", path.display())?;
+            for line in validation.error.iter().flat_map(|error| error.lines()) {
+                writeln!(out, "    {}", line)?;
+            }
+            for mismatch in validation.mismatches {
+                writeln!(out, "    {}", mismatch)?;
+            }
+        }
+    }
+
+    if !json {
+        writeln!(out, "{} files, {} invalid", files.len(), num_invalid)?;
+    }
+
+    Ok(if num_invalid > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// `paths`, with each directory replaced by the `.gbx` files under it whose names pass `filter`
+fn expand_paths(paths: &[PathBuf], filter: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut found = Vec::new();
+            find_gbx_files(path, &mut found);
+            files.extend(found.into_iter().filter(|file| {
+                file.file_name()
+                    .is_some_and(|name| filter(&name.to_string_lossy()))
+            }));
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files
+}
+
+/// Collects every `.gbx` file under `dir`, in a stable order
+fn find_gbx_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let mut entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect::<Vec<_>>(),
+        Err(err) => {
+            eprintln!("{}: {}", dir.display(), err);
+            return;
+        }
+    };
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            find_gbx_files(&path, files);
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("gbx"))
+        {
+            files.push(path);
+        }
+    }
+}
+
+fn validate_file(path: &Path) -> Validation {
+    let mut validation = Validation {
+        path: path.to_owned(),
+        class_id: None,
+        error: None,
+        mismatches: Vec::new(),
+        report: None,
+    };
+
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) => {
+            validation.error = Some(err.to_string());
+            return validation;
+        }
+    };
+
+    // one broken file shouldn't stop a scan of thousands
+    let parsed = std::panic::catch_unwind(move || {
+        let node = Node::read_stream(file)?;
+        let class_id = node.header().class_id;
+        let (parsed, report) = node.parse_with_report()?;
+        let mismatches = match parsed {
+            CGame::CtnChallenge(map) => map
+                .header_xml()
+                .map(|xml| xml.mismatches(&map))
+                .unwrap_or_default()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok::<_, GbxError>((class_id, mismatches, report))
+    });
+
+    match parsed {
+        Ok(Ok((class_id, mismatches, report))) => {
+            validation.class_id = Some(class_id);
+            validation.mismatches = mismatches;
+            validation.report = Some(report);
+        }
+        Ok(Err(err)) => validation.error = Some(err.to_string()),
+        Err(_) => validation.error = Some("parser panicked".to_owned()),
+    }
+
+    validation
+}
+
+fn uid(out: &mut impl Write, paths: &[PathBuf]) -> io::Result<ExitCode> {
+    let files = expand_paths(paths, |name| {
+        name.to_ascii_lowercase().ends_with(".map.gbx")
+    });
+
+    let mut code = ExitCode::SUCCESS;
+    for path in files.iter() {
+        let uid = read(path).and_then(|data| {
+            let node = Node::read_header(&data).map_err(gbx_error(path))?;
+            match node.parse_header().map_err(gbx_error(path))? {
+                CGame::CtnChallenge(map) => map
+                    .map_info
+                    .map(|info| info.id.into_owned())
+                    .ok_or_else(|| io::Error::other(file_error(path, "map has no UID"))),
+                _ => Err(io::Error::other(file_error(path, "not a map"))),
+            }
+        });
+
+        match uid {
+            Ok(uid) => writeln!(out, "{}\t{}", uid, path.display())?,
+            Err(err) => {
+                eprintln!("{}", err);
+                code = ExitCode::FAILURE;
+            }
+        }
+    }
+    Ok(code)
+}
@@ -0,0 +1,69 @@
+//! Numeric collection (environment) IDs, as stored in lookback strings
+//!
+//! Collections can be referred to by a number instead of a name; these are the known ones.
+
+const COLLECTIONS: &[(u32, &str)] = &[
+    (0, "Desert"),
+    (1, "Snow"),
+    (2, "Rally"),
+    (3, "Island"),
+    (4, "Bay"),
+    (5, "Coast"),
+    (6, "Stadium"),
+    (7, "Basic"),
+    (8, "Plain"),
+    (9, "Moon"),
+    (10, "Toy"),
+    (11, "Valley"),
+    (12, "Canyon"),
+    (13, "Lagoon"),
+    (14, "Deprecated_Arena"),
+    (15, "TMCommon"),
+    (16, "Canyon4"),
+    (17, "Canyon256"),
+    (18, "Valley4"),
+    (19, "Valley256"),
+    (20, "Lagoon4"),
+    (21, "Lagoon256"),
+    (22, "Stadium4"),
+    (23, "Stadium256"),
+    // Trackmania (2020) Stadium
+    (26, "Stadium"),
+    (100, "History"),
+    (101, "Society"),
+    (102, "Galaxy"),
+    (103, "QuizCommon"),
+    (104, "Quiz"),
+    (200, "Gothic"),
+    (201, "Paris"),
+    (202, "Storm"),
+    (203, "Cryo"),
+    (204, "Meteor"),
+    (205, "Meteor4"),
+    (206, "Meteor64"),
+    (207, "Meteor256"),
+    (299, "SMCommon"),
+    (10000, "Vehicles"),
+    (10001, "Orbital"),
+    (10002, "Actors"),
+    (10003, "Common"),
+];
+
+/// Returns the name of the collection with the numeric ID `id`
+pub fn name(id: u32) -> Option<&'static str> {
+    COLLECTIONS
+        .iter()
+        .find(|(known, _)| *known == id)
+        .map(|(_, name)| *name)
+}
+
+/// Returns the numeric ID of the collection called `name`
+///
+/// Names shared by several IDs (like `"Stadium"`) resolve to the most recent one.
+pub fn id(name: &str) -> Option<u32> {
+    COLLECTIONS
+        .iter()
+        .rev()
+        .find(|(_, known)| *known == name)
+        .map(|(id, _)| *id)
+}
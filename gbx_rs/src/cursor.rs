@@ -1,41 +1,63 @@
 use crate::{
-    parse::{self, CGame},
-    Context, GbxError, GbxErrorInner, Meta,
+    collection,
+    parse::{self, CGame, SharedNode},
+    registry::ChunkRegistry,
+    ByteFormat, Context, ErrorLocation, ExternalRef, FileRef, GbxError, GbxErrorInner, Meta,
+    NodeRef, ParseLimits, ParseReport, SkippedChunk,
 };
 use byteorder::{ReadBytesExt, LE};
 use std::{
+    cell::{Cell, RefCell, RefMut},
     collections::HashMap,
     fmt::Debug,
     io::{Cursor, Seek},
     ops::{Deref, DerefMut},
+    rc::Rc,
+    sync::Arc,
 };
 
-pub trait CursorExt {
-    fn peek_u32_le(&mut self) -> Result<u32, GbxError>;
-}
+pub(crate) const SKIP: u32 = 0x53_4b_49_50;
 
-impl CursorExt for Cursor<&'_ [u8]> {
-    fn peek_u32_le(&mut self) -> Result<u32, GbxError> {
-        let value = self.read_u32::<LE>()?;
-        self.seek_relative(-4)?;
-        Ok(value)
-    }
+/// Lookback strings seen so far, shared by every chunk of a header or body
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Lookback<'node> {
+    pub version: Option<u32>,
+    pub strings: Vec<&'node str>,
 }
 
 #[derive(Clone)]
 pub(crate) struct BodyCursor<'node> {
     inner: Cursor<&'node [u8]>,
-    lookback_version: Option<u32>,
-    strings: Vec<&'node str>,
-    nodes: HashMap<i32, CGame<'node>>,
+    byte_format: ByteFormat,
+    header: bool,
+    chunk_end: Option<u64>,
+    lookback: Lookback<'node>,
+    /// Nodes read so far, shared with the cursors of nodes nested in this one, since node
+    /// indices are the same throughout the file
+    nodes: Rc<RefCell<HashMap<i32, SharedNode<'node>>>>,
+    /// Nodes in other files, by the index node references use for them
+    external_refs: Rc<HashMap<i32, ExternalRef>>,
+    /// Where this cursor's data starts within the body, for encapsulated data
+    offset: u64,
+    /// Shared with every cursor encapsulated from this one
+    report: Rc<RefCell<ParseReport>>,
+    registry: Option<Arc<ChunkRegistry>>,
+    limits: ParseLimits,
+    /// Nodes parsed so far, shared with every cursor encapsulated from this one
+    num_nodes: Rc<Cell<usize>>,
+    /// How many nodes the current one is nested in
+    depth: usize,
+    /// Chunks being parsed, outermost first, shared with every cursor encapsulated from this one
+    chunks: Rc<RefCell<Vec<u32>>>,
 }
 
 impl Debug for BodyCursor<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("BodyCursor")
-            .field("lookback_version", &self.lookback_version)
-            .field("strings", &self.strings)
-            .field("nodes", &self.nodes)
+            .field("byte_format", &self.byte_format)
+            .field("header", &self.header)
+            .field("lookback", &self.lookback)
+            .field("nodes", &self.nodes.borrow())
             .finish()
     }
 }
@@ -58,17 +80,274 @@ impl<'node> BodyCursor<'node> {
     pub fn new(cursor: Cursor<&'node [u8]>) -> Self {
         BodyCursor {
             inner: cursor,
-            lookback_version: None,
-            strings: Vec::new(),
-            nodes: HashMap::new(),
+            byte_format: ByteFormat::Binary,
+            header: false,
+            chunk_end: None,
+            lookback: Lookback::default(),
+            nodes: Rc::default(),
+            external_refs: Rc::default(),
+            offset: 0,
+            report: Rc::default(),
+            registry: None,
+            limits: ParseLimits::default(),
+            num_nodes: Rc::default(),
+            depth: 0,
+            chunks: Rc::default(),
+        }
+    }
+
+    pub fn new_header(cursor: Cursor<&'node [u8]>) -> Self {
+        BodyCursor {
+            header: true,
+            ..BodyCursor::new(cursor)
+        }
+    }
+
+    /// Some classes reuse the same chunk ID for different data in the header and the body
+    pub fn is_header(&self) -> bool {
+        self.header
+    }
+
+    pub fn with_byte_format(self, byte_format: ByteFormat) -> Self {
+        BodyCursor {
+            byte_format,
+            ..self
+        }
+    }
+
+    pub fn set_byte_format(&mut self, byte_format: ByteFormat) {
+        self.byte_format = byte_format;
+    }
+
+    /// Records skipped chunks into `report`, e.g. to share one report between header and body
+    pub fn with_report(self, report: Rc<RefCell<ParseReport>>) -> Self {
+        BodyCursor { report, ..self }
+    }
+
+    pub fn report(&self) -> RefMut<'_, ParseReport> {
+        self.report.borrow_mut()
+    }
+
+    pub fn with_registry(self, registry: Option<Arc<ChunkRegistry>>) -> Self {
+        BodyCursor { registry, ..self }
+    }
+
+    pub fn with_external_refs(self, external_refs: &[ExternalRef]) -> Self {
+        let external_refs = external_refs
+            .iter()
+            .map(|external_ref| (external_ref.node_index as i32, external_ref.clone()))
+            .collect();
+        BodyCursor {
+            external_refs: Rc::new(external_refs),
+            ..self
+        }
+    }
+
+    pub fn with_limits(self, limits: ParseLimits) -> Self {
+        BodyCursor { limits, ..self }
+    }
+
+    pub fn limits(&self) -> ParseLimits {
+        self.limits
+    }
+
+    /// Extra chunk handlers, shared with every cursor encapsulated from this one
+    pub fn registry(&self) -> Option<Arc<ChunkRegistry>> {
+        self.registry.clone()
+    }
+
+    /// The position within the whole body (or file, for the header), even in encapsulated data
+    pub fn absolute_position(&self) -> u64 {
+        self.offset + self.position()
+    }
+
+    /// Where parsing is now, for errors
+    pub fn location(&self) -> ErrorLocation {
+        ErrorLocation {
+            offset: self.absolute_position(),
+            header: self.header,
+            chunks: self.chunks.borrow().clone(),
+        }
+    }
+
+    /// Parses chunk `chunk_id` with `parse`, adding the location to any error it returns
+    pub fn in_chunk<T>(
+        &mut self,
+        chunk_id: u32,
+        parse: impl FnOnce(&mut Self) -> Result<T, GbxError>,
+    ) -> Result<T, GbxError> {
+        let chunk_end = self.chunk_end;
+        self.chunks.borrow_mut().push(chunk_id);
+        let result = parse(self).map_err(|err| err.with_location(|| self.location()));
+        self.chunks.borrow_mut().pop();
+        // chunks of nested nodes set their own end
+        self.chunk_end = chunk_end;
+        result
+    }
+
+    /// A fresh cursor over encapsulated data, with its own lookback strings and node references
+    pub fn encapsulated(&self, data: &'node [u8]) -> BodyCursor<'node> {
+        // encapsulated data is normally a slice of this cursor's data
+        let outer = self.get_ref().as_ptr_range();
+        let start = data.as_ptr();
+        let offset = if outer.contains(&start) {
+            self.offset + (start as usize - outer.start as usize) as u64
+        } else {
+            0
+        };
+
+        BodyCursor {
+            offset,
+            report: self.report.clone(),
+            registry: self.registry.clone(),
+            limits: self.limits,
+            num_nodes: self.num_nodes.clone(),
+            depth: self.depth,
+            chunks: self.chunks.clone(),
+            ..BodyCursor::new(Cursor::new(data)).with_byte_format(self.byte_format)
+        }
+    }
+
+    /// In text GBX files, values are whitespace-separated decimal tokens. Strings and
+    /// other raw data keep their length prefix and follow a single separator.
+    fn read_token(&mut self) -> Result<&'node str, GbxError> {
+        let data = *self.get_ref();
+        let mut start = self.position() as usize;
+        while start < data.len() && data[start].is_ascii_whitespace() {
+            start += 1;
+        }
+
+        let mut end = start;
+        while end < data.len() && !data[end].is_ascii_whitespace() {
+            end += 1;
+        }
+
+        if start == end {
+            return Err(GbxErrorInner::InvalidText { position: start }.into());
+        }
+
+        self.set_position(end as u64);
+        std::str::from_utf8(&data[start..end]).context("Reading text token")
+    }
+
+    /// An integer token, which fails if it doesn't fit in `T` rather than being truncated
+    fn read_text_integer<T: TryFrom<i128>>(&mut self) -> Result<T, GbxError> {
+        let position = self.position() as usize;
+        let token = self.read_token()?;
+        token
+            .parse::<i128>()
+            .ok()
+            .and_then(|value| T::try_from(value).ok())
+            .ok_or_else(|| GbxErrorInner::InvalidText { position }.into())
+    }
+
+    fn skip_text_separator(&mut self) {
+        if self.byte_format != ByteFormat::Text {
+            return;
+        }
+
+        let data = *self.get_ref();
+        let position = self.position() as usize;
+        if data
+            .get(position..)
+            .is_some_and(|rest| rest.starts_with(b"\r\n"))
+        {
+            self.set_position(position as u64 + 2);
+        } else if data.get(position).is_some_and(u8::is_ascii_whitespace) {
+            self.set_position(position as u64 + 1);
+        }
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, GbxError> {
+        match self.byte_format {
+            ByteFormat::Binary => Ok(self.inner.read_u8()?),
+            ByteFormat::Text => self.read_text_integer(),
         }
     }
 
+    pub fn read_u16(&mut self) -> Result<u16, GbxError> {
+        match self.byte_format {
+            ByteFormat::Binary => Ok(self.inner.read_u16::<LE>()?),
+            ByteFormat::Text => self.read_text_integer(),
+        }
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, GbxError> {
+        match self.byte_format {
+            ByteFormat::Binary => Ok(self.inner.read_u32::<LE>()?),
+            ByteFormat::Text => self.read_text_integer(),
+        }
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, GbxError> {
+        match self.byte_format {
+            ByteFormat::Binary => Ok(self.inner.read_i32::<LE>()?),
+            ByteFormat::Text => self.read_text_integer(),
+        }
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, GbxError> {
+        match self.byte_format {
+            ByteFormat::Binary => Ok(self.inner.read_u64::<LE>()?),
+            ByteFormat::Text => self.read_text_integer(),
+        }
+    }
+
+    pub fn read_u128(&mut self) -> Result<u128, GbxError> {
+        match self.byte_format {
+            ByteFormat::Binary => Ok(self.inner.read_u128::<LE>()?),
+            ByteFormat::Text => self.read_text_integer(),
+        }
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, GbxError> {
+        match self.byte_format {
+            ByteFormat::Binary => Ok(self.inner.read_f32::<LE>()?),
+            ByteFormat::Text => {
+                let position = self.position() as usize;
+                let token = self.read_token()?;
+                token
+                    .parse()
+                    .map_err(|_| GbxErrorInner::InvalidText { position }.into())
+            }
+        }
+    }
+
+    pub fn peek_u32(&mut self) -> Result<u32, GbxError> {
+        let position = self.position();
+        let value = self.read_u32();
+        self.set_position(position);
+        value
+    }
+
+    /// Reads the size of a skippable chunk if one starts here
+    pub fn read_skippable(&mut self) -> Result<Option<u32>, GbxError> {
+        let position = self.position();
+        let is_skippable = match self.byte_format {
+            ByteFormat::Binary => self.inner.read_u32::<LE>().ok() == Some(SKIP),
+            ByteFormat::Text => self.read_token().ok() == Some("SKIP"),
+        };
+
+        if !is_skippable {
+            self.set_position(position);
+            return Ok(None);
+        }
+
+        let size = self.read_u32().context("Reading skippable data size")?;
+        let start = self.position() as usize;
+        let end = start + size as usize;
+        if end > self.get_ref().len() {
+            return Err(GbxErrorInner::OutOfBounds { start, end }.into());
+        }
+        Ok(Some(size))
+    }
+
     pub fn read_string_exact(&mut self, count: usize) -> Result<&'node str, GbxError> {
+        self.skip_text_separator();
         let start = self.position() as usize;
         let end = start + count as usize;
 
-        if start >= self.get_ref().len() || end >= self.get_ref().len() {
+        if end > self.get_ref().len() {
             return Err(GbxErrorInner::InvalidString { start, end }.into());
         }
 
@@ -79,30 +358,90 @@ impl<'node> BodyCursor<'node> {
         Ok(str)
     }
 
+    /// End of the skippable or header chunk currently being read, if any
+    pub fn chunk_end(&self) -> Option<u64> {
+        self.chunk_end
+    }
+
+    pub fn set_chunk_end(&mut self, chunk_end: Option<u64>) {
+        self.chunk_end = chunk_end;
+    }
+
+    pub fn read_bytes(&mut self, count: usize) -> Result<&'node [u8], GbxError> {
+        self.skip_text_separator();
+        let start = self.position() as usize;
+        let end = start + count;
+
+        if end > self.get_ref().len() {
+            return Err(GbxErrorInner::OutOfBounds { start, end }.into());
+        }
+
+        let bytes = &self.get_ref()[start..end];
+        self.seek_relative(count as i64)
+            .context("Seeking after reading bytes")?;
+        Ok(bytes)
+    }
+
     pub fn read_string(&mut self) -> Result<&'node str, GbxError> {
-        let count = self.read_u32::<LE>().context("Reading string length")?;
-        self.read_string_exact(count as usize)
+        let count = self.read_u32().context("Reading string length")? as usize;
+        if count > self.limits.max_string_length {
+            return Err(GbxErrorInner::StringLengthLimit {
+                length: count,
+                limit: self.limits.max_string_length,
+            }
+            .into());
+        }
+        self.read_string_exact(count)
     }
 
     pub fn read_meta(&mut self) -> Result<Meta<'node>, GbxError> {
         Ok(Meta {
-            id: self.read_lookback_string().context("Reading meta ID")?,
+            id: self
+                .read_lookback_string()
+                .context("Reading meta ID")?
+                .into(),
             collection: self
                 .read_lookback_string()
-                .context("Reading meta collection")?,
-            author: self.read_lookback_string().context("Reading meta author")?,
+                .context("Reading meta collection")?
+                .into(),
+            author: self
+                .read_lookback_string()
+                .context("Reading meta author")?
+                .into(),
         })
     }
 
+    pub fn lookback(&self) -> &Lookback<'node> {
+        &self.lookback
+    }
+
+    pub fn set_lookback(&mut self, lookback: Lookback<'node>) {
+        self.lookback = lookback;
+    }
+
+    /// Nodes read so far, by the index node references use for them
+    pub fn nodes(&self) -> HashMap<i32, SharedNode<'node>> {
+        self.nodes.borrow().clone()
+    }
+
+    /// Replaces the nodes read so far, for every cursor sharing them
+    pub fn set_nodes(&mut self, nodes: HashMap<i32, SharedNode<'node>>) {
+        *self.nodes.borrow_mut() = nodes;
+    }
+
     pub fn read_lookback_string(&mut self) -> Result<&'node str, GbxError> {
-        if self.lookback_version.is_none() {
-            self.lookback_version =
-                Some(self.read_u32::<LE>().context("Reading lookback version")?);
+        self.read_lookback().map(|(str, _)| str)
+    }
+
+    /// A lookback string, and the numeric ID it was stored as if it is a collection name
+    pub fn read_lookback(&mut self) -> Result<(&'node str, Option<u32>), GbxError> {
+        if self.lookback.version.is_none() {
+            self.lookback.version = Some(self.read_u32().context("Reading lookback version")?);
         }
 
-        let index = self.read_u32::<LE>().context("Reading lookback index")?;
+        let index = self.read_u32().context("Reading lookback index")?;
         if index == 0xffff_ffffu32 {
-            return Ok("");
+            return Ok(("", None));
         }
         let index = index as i64;
 
@@ -111,65 +450,120 @@ impl<'node> BodyCursor<'node> {
                 .read_string()
                 .context("Reading first occurrence of lookback string")?;
             tracing::trace!("new string {:?}", str);
-            self.strings.push(str);
-            return Ok(str);
+            self.lookback.strings.push(str);
+            return Ok((str, None));
         }
 
         if (index & 0x3fff) == 0x3fff {
             match index >> 30 {
-                2 => return Ok("Unassigned"),
-                3 => return Ok(""),
+                2 => return Ok(("Unassigned", None)),
+                3 => return Ok(("", None)),
                 _ => return Err(GbxErrorInner::InvalidLookbackString.into()),
             }
         }
 
         if (index >> 30) == 0 {
-            return Ok("TODO: collections");
+            let name = collection::name(index as u32).unwrap_or_else(|| {
+                tracing::warn!("unknown collection ID {}", index);
+                ""
+            });
+            return Ok((name, Some(index as u32)));
         }
 
-        if self.strings.len() > ((index & 0x3fff) - 1) as usize {
-            Ok(self.strings[((index & 0x3fff) - 1) as usize])
+        if self.lookback.strings.len() > ((index & 0x3fff) - 1) as usize {
+            Ok((self.lookback.strings[((index & 0x3fff) - 1) as usize], None))
         } else {
-            Ok("")
+            Ok(("", None))
+        }
+    }
+
+    pub fn expect_node_ref<N: parse::Parsable<'node>>(
+        &mut self,
+    ) -> Result<Option<NodeRef<N>>, GbxError> {
+        match self.read_node_ref()? {
+            None => Ok(None),
+            Some(SharedNode::External(external_ref)) => {
+                tracing::debug!("external node {:?}", external_ref);
+                Ok(Some(NodeRef::External(external_ref)))
+            }
+            Some(node) => Ok(Some(NodeRef::Local(N::coerce_shared(node)?))),
         }
     }
 
-    pub fn expect_node_ref<N: parse::Parsable<'node>>(&mut self) -> Result<Option<N>, GbxError> {
-        let Some(node) = self.read_node_ref()? else {
+    /// A node stored inline, without a node reference index
+    pub fn expect_node<N: parse::Parsable<'node>>(&mut self) -> Result<Option<N>, GbxError> {
+        let class_id = self.read_i32().context("Reading node class ID")?;
+        if class_id == -1 {
             return Ok(None);
-        };
+        }
+
+        let node = self.parse_nested(class_id as u32)?;
         Ok(Some(N::coerce(node)?))
     }
 
-    pub fn read_node_ref(&mut self) -> Result<Option<CGame<'node>>, GbxError> {
-        let index = self
-            .read_i32::<LE>()
-            .context("Reading node reference index")?;
+    /// The class of the node the next node reference reads for the first time, without moving.
+    /// `None` for null references, and references to nodes already read or in other files.
+    pub fn peek_new_node_class(&mut self) -> Result<Option<u32>, GbxError> {
+        let position = self.position();
+        let index = self.read_i32().context("Reading node reference index")?;
+        let class_id = if index >= 0
+            && !self.nodes.borrow().contains_key(&index)
+            && !self.external_refs.contains_key(&index)
+        {
+            Some(self.read_u32().context("Reading node reference class ID")?)
+        } else {
+            None
+        };
+        self.set_position(position);
+        Ok(class_id)
+    }
+
+    /// Reads a node the first time it is referenced; later references share it
+    pub fn read_node_ref(&mut self) -> Result<Option<SharedNode<'node>>, GbxError> {
+        let index = self.read_i32().context("Reading node reference index")?;
 
         if index == -1 {
             return Ok(None);
         }
 
-        if let Some(cgame) = self.nodes.get(&index) {
+        if let Some(cgame) = self.nodes.borrow().get(&index) {
             return Ok(Some(cgame.clone()));
         }
 
+        if let Some(external_ref) = self.external_refs.get(&index) {
+            return Ok(Some(SharedNode::External(external_ref.clone())));
+        }
+
         if index >= 0 {
-            let class_id = self
-                .read_u32::<LE>()
-                .context("Reading node reference class ID")?;
+            let class_id = self.read_u32().context("Reading node reference class ID")?;
 
             let position = self.position() as usize;
-
-            let mut cursor = BodyCursor::new(Cursor::new(&self.get_ref()[position..]));
-            let node = CGame::parse(&mut cursor, class_id)?;
+            let rest = self
+                .get_ref()
+                .get(position..)
+                .ok_or(GbxErrorInner::OutOfBounds {
+                    start: position,
+                    end: self.get_ref().len(),
+                })?;
+
+            // the node's own references still index into this file, so it shares the nodes read
+            // so far, and its lookback strings carry on from the ones read so far
+            let mut cursor = BodyCursor {
+                nodes: self.nodes.clone(),
+                external_refs: self.external_refs.clone(),
+                lookback: std::mem::take(&mut self.lookback),
+                ..self.encapsulated(rest)
+            };
+            let node = cursor.parse_nested(class_id);
+            self.lookback = std::mem::take(&mut cursor.lookback);
+            let node = SharedNode::from(node?);
 
             let forward = cursor.position() as i64;
             tracing::trace!("read {} bytes of {:08x}", forward, class_id);
             self.seek_relative(forward)
                 .context("Seeking after read node reference")?;
 
-            self.nodes.insert(index, node.clone());
+            self.nodes.borrow_mut().insert(index, node.clone());
 
             Ok(Some(node))
         } else {
@@ -177,12 +571,25 @@ impl<'node> BodyCursor<'node> {
         }
     }
 
+    /// Parses a node inside the current one, counting it towards the limits
+    fn parse_nested(&mut self, class_id: u32) -> Result<CGame<'node>, GbxError> {
+        let num_nodes = self.num_nodes.get() + 1;
+        if num_nodes > self.limits.max_nodes {
+            return Err(GbxErrorInner::NodeCountLimit(self.limits.max_nodes).into());
+        }
+        self.num_nodes.set(num_nodes);
+
+        if self.depth >= self.limits.max_depth {
+            return Err(GbxErrorInner::DepthLimit(self.limits.max_depth).into());
+        }
+        self.depth += 1;
+        let node = CGame::parse(self, class_id);
+        self.depth -= 1;
+        node
+    }
+
     pub fn read_int3(&mut self) -> Result<[u32; 3], GbxError> {
-        Ok([
-            self.read_u32::<LE>()?,
-            self.read_u32::<LE>()?,
-            self.read_u32::<LE>()?,
-        ])
+        Ok([self.read_u32()?, self.read_u32()?, self.read_u32()?])
     }
 
     pub fn read_byte3(&mut self) -> Result<[u8; 3], GbxError> {
@@ -190,33 +597,125 @@ impl<'node> BodyCursor<'node> {
     }
 
     pub fn read_vec2(&mut self) -> Result<[f32; 2], GbxError> {
-        Ok([self.read_f32::<LE>()?, self.read_f32::<LE>()?])
+        Ok([self.read_f32()?, self.read_f32()?])
+    }
+
+    pub fn read_vec3(&mut self) -> Result<[f32; 3], GbxError> {
+        Ok([self.read_f32()?, self.read_f32()?, self.read_f32()?])
     }
 
-    pub fn read_file_ref(&mut self) -> Result<&'node str, GbxError> {
+    pub fn read_bool(&mut self) -> Result<bool, GbxError> {
+        Ok(self.read_u32()? != 0)
+    }
+
+    pub fn read_file_ref(&mut self) -> Result<FileRef<'node>, GbxError> {
         let version = self.read_u8().context("Reading file reference version")?;
+        let mut checksum = None;
         if version >= 3 {
-            self.seek_relative(32)
-                .context("Seeking past file reference checksum")?; // checksum
+            let bytes = self
+                .read_bytes(32)
+                .context("Reading file reference checksum")?;
+            checksum = <[u8; 32]>::try_from(bytes)
+                .ok()
+                .filter(|checksum| *checksum != [0; 32]);
         }
         let file_path = self.read_string().context("Reading file reference path")?;
+        let mut locator_url = None;
         if (file_path.len() > 0 && version >= 1) || version >= 3 {
-            let _locator_url = self
-                .read_string()
-                .context("Reading file reference locator URL")?;
+            locator_url = Some(
+                self.read_string()
+                    .context("Reading file reference locator URL")?,
+            )
+            .filter(|url| !url.is_empty());
+        }
+        Ok(FileRef {
+            path: file_path.into(),
+            checksum,
+            locator_url: locator_url.map(Into::into),
+        })
+    }
+
+    /// The next u32, for scans through data that isn't understood. In text files, tokens that
+    /// aren't one, like strings and floats, are `None` rather than an error.
+    fn peek_scan(&mut self) -> Result<Option<u32>, GbxError> {
+        match self.peek_u32() {
+            Ok(value) => Ok(Some(value)),
+            Err(err)
+                if self.byte_format == ByteFormat::Text
+                    && matches!(*err, GbxErrorInner::InvalidText { .. }) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Moves a scan along by a byte, or by a token in text files, so it never starts reading
+    /// in the middle of one
+    fn advance_scan(&mut self) -> Result<(), GbxError> {
+        match self.byte_format {
+            ByteFormat::Binary => {
+                self.inner.read_u8()?;
+            }
+            ByteFormat::Text => {
+                self.read_token()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips the rest of a nested node that isn't understood, up to its end marker, and reports
+    /// `skip_chunk_id` as force skipped. Nodes inside it have end markers too, so it's the first
+    /// one followed by `trailing` u32s and then `next_chunk_id`. Stops before the trailing u32s.
+    pub fn force_skip_node(
+        &mut self,
+        skip_chunk_id: u32,
+        trailing: usize,
+        next_chunk_id: u32,
+    ) -> Result<(), GbxError> {
+        let start = self.position();
+        let offset = self.absolute_position();
+        loop {
+            if self.peek_scan().context("Force skipping node")? == Some(parse::LAST_CHUNK_ID) {
+                let position = self.position();
+                self.read_u32()?;
+                let next = (0..trailing)
+                    .try_for_each(|_| self.read_u32().map(|_| ()))
+                    .and_then(|()| self.peek_scan());
+                self.set_position(position);
+                if matches!(next, Ok(Some(chunk_id)) if chunk_id == next_chunk_id) {
+                    break;
+                }
+            }
+            self.advance_scan().context("Force skipping node")?;
         }
-        Ok(file_path)
+        self.read_u32()?;
+        tracing::warn!("Force skipped chunk {:08x}", skip_chunk_id);
+
+        self.report().force_skipped_chunks.push(SkippedChunk {
+            chunk_id: skip_chunk_id,
+            class_id: parse::class_wrap(skip_chunk_id & 0xffff_f000),
+            size: (self.position() - start) as u32,
+            offset,
+            header: self.header,
+        });
+
+        Ok(())
     }
 
     pub fn force_skip(&mut self, skip_chunk_id: u32) -> Result<(), GbxError> {
+        let start = self.position();
+        let offset = self.absolute_position();
         loop {
-            let next_chunk_id_maybe = self.peek_u32_le().context("Force skipping chunk")?;
-            if (next_chunk_id_maybe & 0xffff_ff00) == (skip_chunk_id & 0xffff_ff00) {
+            let next_chunk_id_maybe = self.peek_scan().context("Force skipping chunk")?;
+            if next_chunk_id_maybe
+                .is_some_and(|chunk_id| (chunk_id & 0xffff_ff00) == (skip_chunk_id & 0xffff_ff00))
+            {
                 tracing::warn!("Force skipped chunk {:08x}", skip_chunk_id);
                 break;
             }
-            if next_chunk_id_maybe == 0xfacade01
-                && self.peek_u32_le().is_err_and(|err| match &*err {
+            if next_chunk_id_maybe == Some(0xfacade01)
+                && self.peek_scan().is_err_and(|err| match &*err {
                     GbxErrorInner::Io(io) => {
                         matches!(io.kind(), std::io::ErrorKind::UnexpectedEof)
                     }
@@ -227,9 +726,18 @@ impl<'node> BodyCursor<'node> {
                 tracing::warn!("Reached end of file early");
                 break;
             }
-            let _skipped_byte = self.read_u8().context("Force skipping media tracker")?;
+            self.advance_scan()
+                .context("Force skipping media tracker")?;
         }
 
+        self.report().force_skipped_chunks.push(SkippedChunk {
+            chunk_id: skip_chunk_id,
+            class_id: parse::class_wrap(skip_chunk_id & 0xffff_f000),
+            size: (self.position() - start) as u32,
+            offset,
+            header: self.header,
+        });
+
         Ok(())
     }
 }
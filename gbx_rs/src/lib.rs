@@ -1,13 +1,22 @@
-use byteorder::{ReadBytesExt, LE};
 use cursor::BodyCursor;
 use std::{
+    borrow::Cow,
+    cell::RefCell,
     fmt::{Debug, Display},
-    io::{Cursor, Read, Seek},
-    ops::Deref,
+    io::{BufRead, BufReader, Cursor, Read, Seek},
+    ops::{Deref, Range},
+    rc::Rc,
+    sync::{Arc, OnceLock},
 };
 
+mod base64_bytes;
+pub mod collection;
 mod cursor;
+mod lzo;
 pub mod parse;
+pub mod registry;
+mod write;
+pub mod xml;
 
 trait Context<T> {
     fn context<C>(self, context: C) -> Result<T, GbxError>
@@ -56,6 +65,76 @@ pub enum GbxError {
         context: String,
         inner: Box<GbxError>,
     },
+    /// Where parsing was when `inner` happened
+    Located {
+        location: Box<ErrorLocation>,
+        inner: Box<GbxError>,
+    },
+}
+
+impl GbxError {
+    /// Where in the file parsing failed, if it got as far as the header or body data
+    pub fn location(&self) -> Option<&ErrorLocation> {
+        match self {
+            GbxError::Root(_) => None,
+            GbxError::Context { inner, .. } => inner.location(),
+            GbxError::Located { location, .. } => Some(location),
+        }
+    }
+
+    /// Byte offset of the failure, see [`ErrorLocation::offset`]
+    pub fn offset(&self) -> Option<u64> {
+        self.location().map(|location| location.offset)
+    }
+
+    /// Whether the failure was in the header rather than the body
+    pub fn is_header(&self) -> Option<bool> {
+        self.location().map(|location| location.header)
+    }
+
+    /// Chunks being parsed at the time of failure, outermost first
+    pub fn chunk_stack(&self) -> &[u32] {
+        self.location()
+            .map_or(&[], |location| location.chunks.as_slice())
+    }
+
+    /// Only the innermost location is kept, as that's where the failure actually was
+    pub(crate) fn with_location(self, location: impl FnOnce() -> ErrorLocation) -> GbxError {
+        if self.location().is_some() {
+            return self;
+        }
+        GbxError::Located {
+            location: Box::new(location()),
+            inner: Box::new(self),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct ErrorLocation {
+    /// From the start of the decompressed body, or of the file for the header
+    pub offset: u64,
+    pub header: bool,
+    /// Full IDs of the chunks being parsed, outermost first. Each includes its class ID.
+    pub chunks: Vec<u32>,
+}
+
+impl Display for ErrorLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "at byte {} ({:#x}) of the {}",
+            self.offset,
+            self.offset,
+            if self.header { "header" } else { "body" }
+        )?;
+        for (i, chunk_id) in self.chunks.iter().enumerate() {
+            let separator = if i == 0 { ", in chunk " } else { " > " };
+            write!(f, "{}{:08x}", separator, chunk_id)?;
+        }
+        Ok(())
+    }
 }
 
 impl<T: Into<GbxErrorInner>> From<T> for GbxError {
@@ -72,6 +151,10 @@ impl Display for GbxError {
                 Display::fmt(inner, f)?;
                 write!(f, "\n  {}", context)
             }
+            GbxError::Located { location, inner } => {
+                Display::fmt(inner, f)?;
+                write!(f, "\n  {}", location)
+            }
         }
     }
 }
@@ -82,7 +165,7 @@ impl Deref for GbxError {
     fn deref(&self) -> &Self::Target {
         match self {
             GbxError::Root(inner) => inner,
-            GbxError::Context { inner, .. } => {
+            GbxError::Context { inner, .. } | GbxError::Located { inner, .. } => {
                 let box_ref = Box::as_ref(inner);
                 <GbxError as Deref>::deref(box_ref)
             }
@@ -108,8 +191,8 @@ pub enum GbxErrorInner {
     #[error("Could not decompress")]
     Lzo(#[from] lzokay_native::Error),
 
-    #[error("Not compressed - not sure what to do with uncompressed gbx data yet")]
-    NotCompressed,
+    #[error("Compressed reference tables are not supported")]
+    CompressedRefTable,
 
     #[error("Invalid byte format {0}")]
     InvalidByteFormat(u8),
@@ -140,6 +223,71 @@ pub enum GbxErrorInner {
 
     #[error("Invalid node reference")]
     InvalidNodeRef,
+
+    #[error("Data from {start:08x} to {end:08x} is out of bounds")]
+    OutOfBounds { start: usize, end: usize },
+
+    #[error("Invalid text value at {position:08x}")]
+    InvalidText { position: usize },
+
+    #[error("Could not read embedded archive")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error("Writing text GBX files is not supported")]
+    WriteText,
+
+    #[error("Writing files with external nodes is not supported")]
+    WriteExternalNodes,
+
+    #[error("Writing changes to {0} is not supported")]
+    WriteUnsupportedField(&'static str),
+
+    #[error("Writing {0} is not supported by the original file, or not with the new value")]
+    WriteUnstoredField(&'static str),
+
+    #[error("Edited lookback strings conflict with the original data of chunk {0:08x}")]
+    LookbackConflict(u32),
+
+    #[error("Registered chunk handler failed: {0}")]
+    Handler(String),
+
+    #[error("Decompressed body of {size} bytes is over the limit of {limit} bytes")]
+    DecompressedSizeLimit { size: usize, limit: usize },
+
+    #[error("String of {length} bytes is over the limit of {limit} bytes")]
+    StringLengthLimit { length: usize, limit: usize },
+
+    #[error("More than {0} nodes")]
+    NodeCountLimit(usize),
+
+    #[error("Nodes nested more than {0} deep")]
+    DepthLimit(usize),
+
+    #[error("Compressed body is malformed")]
+    InvalidCompressedData,
+
+    #[error("No folder {0} in the reference table")]
+    InvalidFolderIndex(u32),
+
+    #[error("Chunk {0:08x} has data that isn't understood, and no size to skip it by")]
+    UnknownChunkData(u32),
+
+    #[error("Block coordinate {0} is outside of any map")]
+    InvalidBlockCoord(u32),
+}
+
+impl GbxErrorInner {
+    /// Whether a [`ParseLimits`] limit was reached, rather than the data being malformed.
+    /// Parsing never recovers from these, so files can't get past the limits.
+    pub fn is_limit(&self) -> bool {
+        matches!(
+            self,
+            GbxErrorInner::DecompressedSizeLimit { .. }
+                | GbxErrorInner::StringLengthLimit { .. }
+                | GbxErrorInner::NodeCountLimit(_)
+                | GbxErrorInner::DepthLimit(_)
+        )
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -160,6 +308,15 @@ impl TryFrom<u8> for ByteFormat {
     }
 }
 
+impl From<ByteFormat> for u8 {
+    fn from(value: ByteFormat) -> Self {
+        match value {
+            ByteFormat::Binary => b'B',
+            ByteFormat::Text => b'T',
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Compression {
     Compressed,
@@ -178,28 +335,166 @@ impl TryFrom<u8> for Compression {
     }
 }
 
-#[allow(unused)]
+impl From<Compression> for u8 {
+    fn from(value: Compression) -> Self {
+        match value {
+            Compression::Compressed => b'C',
+            Compression::Uncompressed => b'U',
+        }
+    }
+}
+
 #[derive(Debug)]
-struct Header {
-    version: u16,
-    byte_format: ByteFormat,
-    body_compression: Compression,
-    class_id: u32,
-    chunks: Vec<HeaderChunk>,
-    num_nodes: u32,
-    num_external_nodes: u32,
+#[non_exhaustive]
+pub struct Header {
+    pub version: u16,
+    pub byte_format: ByteFormat,
+    pub ref_table_compression: Compression,
+    pub body_compression: Compression,
+    unknown_flag: Option<u8>,
+    pub class_id: u32,
+    pub chunks: Vec<HeaderChunk>,
+    pub num_nodes: u32,
+    pub num_external_nodes: u32,
+    /// How many folders up from this file the folders of `external_refs` start
+    pub ancestor_level: u32,
+    pub external_refs: Vec<ExternalRef>,
+    /// How deeply the reference table's folders are nested, checked against the limits the
+    /// node is parsed with
+    folder_depth: usize,
 }
 
-#[allow(unused)]
 #[derive(Debug)]
-struct HeaderChunk {
-    id: u32,
-    size: u32,
-    heavy: bool,
+#[non_exhaustive]
+pub struct HeaderChunk {
+    /// The chunk ID without the class ID
+    pub id: u32,
+    /// The chunk ID as stored, with its class ID. Chunks of parent classes, like an item's
+    /// collector chunks, have a different class ID from the file, and older files may use a
+    /// class's legacy ID.
+    pub full_id: u32,
+    pub size: u32,
+    /// Heavy chunks are skipped by the game when it only lists files
+    pub heavy: bool,
     data_start: u64,
 }
 
-fn parse_header<B: AsRef<[u8]>>(cursor: &mut Cursor<B>) -> Result<Header, GbxError> {
+/// A node stored in another file, from the header's reference table. Fields that hold a
+/// particular class have it as [`NodeRef::External`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct ExternalRef {
+    /// The index node references in the body use for it
+    pub node_index: u32,
+    pub flags: u32,
+    /// `None` for the game's own resources
+    pub file_name: Option<String>,
+    /// Which of the game's own resources it is
+    pub resource_index: Option<u32>,
+    /// Path of the folder the file is in, relative to [`Header::ancestor_level`]
+    pub folder: Option<String>,
+    pub use_file: bool,
+}
+
+impl ExternalRef {
+    /// The file's path, relative to [`Header::ancestor_level`]
+    pub fn path(&self) -> Option<String> {
+        let file_name = self.file_name.as_deref()?;
+        Some(match self.folder.as_deref() {
+            Some(folder) if !folder.is_empty() => format!("{folder}/{file_name}"),
+            _ => file_name.to_owned(),
+        })
+    }
+}
+
+/// A node reference of a particular class, to a node in this file or one in another file
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum NodeRef<N> {
+    Local(Arc<N>),
+    External(ExternalRef),
+}
+
+impl<N> NodeRef<N> {
+    /// The node, if it's stored in this file
+    pub fn local(&self) -> Option<&N> {
+        match self {
+            NodeRef::Local(node) => Some(node),
+            NodeRef::External(_) => None,
+        }
+    }
+
+    /// Where the node is stored, if it's in another file
+    pub fn external(&self) -> Option<&ExternalRef> {
+        match self {
+            NodeRef::Local(_) => None,
+            NodeRef::External(external_ref) => Some(external_ref),
+        }
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct BodyChunk {
+    /// The full chunk ID, including the class ID
+    pub id: u32,
+    /// Size of the chunk data, without the chunk ID and any skippable marker
+    pub size: u32,
+    pub skippable: bool,
+}
+
+/// Bounds on the resources a file can make parsing use, for files that can't be trusted
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct ParseLimits {
+    /// Largest body to decompress, in bytes
+    pub max_decompressed_size: usize,
+    /// Longest length-prefixed string, in bytes
+    pub max_string_length: usize,
+    /// Most nodes to parse, counting every node reference read for the first time
+    pub max_nodes: usize,
+    /// How deeply nodes can be nested in one another
+    pub max_depth: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits {
+            max_decompressed_size: 256 << 20,
+            max_string_length: 4 << 20,
+            max_nodes: 100_000,
+            max_depth: 32,
+        }
+    }
+}
+
+/// What a parse had to leave out, to find out which chunks are still worth learning
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct ParseReport {
+    /// Skippable chunks, and header chunks, that no class knows how to read
+    pub skipped_chunks: Vec<SkippedChunk>,
+    /// Chunks that were only known well enough to search for the start of the next chunk
+    pub force_skipped_chunks: Vec<SkippedChunk>,
+    /// `Option` fields of the top-level node that are still `None` after parsing
+    pub missing_fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct SkippedChunk {
+    /// The full chunk ID, including the class ID
+    pub chunk_id: u32,
+    /// The class of the node the chunk belongs to
+    pub class_id: u32,
+    /// Number of bytes skipped
+    pub size: u32,
+    /// Where the skipped bytes start, from the start of the decompressed body, or of the file
+    /// for header chunks
+    pub offset: u64,
+    pub header: bool,
+}
+
+fn parse_header(cursor: &mut BodyCursor) -> Result<Header, GbxError> {
     let mut magic = [0u8; 3];
     cursor.read_exact(&mut magic).context("Reading magic")?;
     if magic != *b"GBX" {
@@ -207,7 +502,7 @@ fn parse_header<B: AsRef<[u8]>>(cursor: &mut Cursor<B>) -> Result<Header, GbxErr
         return Err(GbxErrorInner::NotGbx.into());
     }
 
-    let version = cursor.read_u16::<LE>().context("Reading version")?;
+    let version = cursor.read_u16().context("Reading version")?;
     if version < 3 {
         return Err(GbxErrorInner::VersionNotSupported(version).into());
     }
@@ -215,11 +510,11 @@ fn parse_header<B: AsRef<[u8]>>(cursor: &mut Cursor<B>) -> Result<Header, GbxErr
 
     let byte_format = cursor.read_u8().context("Reading byte format")?;
     tracing::debug!("byte format {:?}", byte_format as char);
-    let byte_format = byte_format.try_into().context("Parsing byte format")?;
+    let byte_format: ByteFormat = byte_format.try_into().context("Parsing byte format")?;
 
-    let _ref_table_compression = cursor.read_u8().context("Reading ref table compression")?;
-    tracing::debug!("ref table compression {:?}", _ref_table_compression as char);
-    let _ref_table_compression: Compression = _ref_table_compression
+    let ref_table_compression = cursor.read_u8().context("Reading ref table compression")?;
+    tracing::debug!("ref table compression {:?}", ref_table_compression as char);
+    let ref_table_compression = ref_table_compression
         .try_into()
         .context("Parsing ref table compression")?;
 
@@ -229,22 +524,25 @@ fn parse_header<B: AsRef<[u8]>>(cursor: &mut Cursor<B>) -> Result<Header, GbxErr
         .try_into()
         .context("Parsing body compression")?;
 
+    let mut unknown_flag = None;
     if version >= 4 {
-        let _unknown = cursor.read_u8().context("Reading unknown 1")?;
-        tracing::debug!("unknown {}", _unknown);
+        let unknown = cursor.read_u8().context("Reading unknown 1")?;
+        tracing::debug!("unknown {}", unknown);
+        unknown_flag = Some(unknown);
     }
 
-    let class_id = cursor.read_u32::<LE>().context("Reading class id")?;
+    // everything after the format flags is written in the byte format
+    cursor.set_byte_format(byte_format);
+
+    let class_id = cursor.read_u32().context("Reading class id")?;
     tracing::debug!("class id 0x{:08x}", class_id);
 
     if version >= 6 {
-        let _user_data_size = cursor.read_u32::<LE>().context("Reading user data size")?;
+        let _user_data_size = cursor.read_u32().context("Reading user data size")?;
         tracing::debug!("user data size {}", _user_data_size);
     }
 
-    let num_header_chunks = cursor
-        .read_u32::<LE>()
-        .context("Reading num header chunks")?;
+    let num_header_chunks = cursor.read_u32().context("Reading num header chunks")?;
     if num_header_chunks == 0 {
         return Err(GbxErrorInner::NoHeaderChunks.into());
     }
@@ -256,13 +554,12 @@ fn parse_header<B: AsRef<[u8]>>(cursor: &mut Cursor<B>) -> Result<Header, GbxErr
         tracing::debug!("chunk {}", i);
 
         let id = cursor
-            .read_u32::<LE>()
-            .with_context(|| format!("Reading chunk {i} ID"))?
-            & 0x0fff;
+            .read_u32()
+            .with_context(|| format!("Reading chunk {i} ID"))?;
         tracing::debug!("  chunk id 0x{:08x}", id);
 
         let chunk_size_heavy = cursor
-            .read_u32::<LE>()
+            .read_u32()
             .with_context(|| format!("Reading chunk {i} size"))?;
         let size = chunk_size_heavy & !0x8000_0000;
         tracing::debug!("  chunk size {}", size);
@@ -271,7 +568,8 @@ fn parse_header<B: AsRef<[u8]>>(cursor: &mut Cursor<B>) -> Result<Header, GbxErr
         tracing::debug!("  heavy {}", heavy);
 
         chunks.push(HeaderChunk {
-            id,
+            id: id & 0xfff,
+            full_id: id,
             size,
             heavy,
             data_start: 0,
@@ -282,31 +580,347 @@ fn parse_header<B: AsRef<[u8]>>(cursor: &mut Cursor<B>) -> Result<Header, GbxErr
         chunk.data_start = cursor.position();
         cursor
             .seek_relative(chunk.size as i64)
-            .with_context(|| format!("Reading chunk {} data", chunk.id))?;
+            .with_context(|| format!("Reading chunk {:08x} data", chunk.full_id))?;
     }
 
-    let num_nodes = cursor.read_u32::<LE>().context("Reading num nodes")?;
+    let num_nodes = cursor.read_u32().context("Reading num nodes")?;
     tracing::debug!("num nodes {}", num_nodes);
-    let num_external_nodes = cursor
-        .read_u32::<LE>()
-        .context("Reading num external nodes")?;
+    let num_external_nodes = cursor.read_u32().context("Reading num external nodes")?;
     tracing::debug!("num external nodes {}", num_external_nodes);
 
+    if num_external_nodes > 0 && ref_table_compression == Compression::Compressed {
+        return Err(GbxErrorInner::CompressedRefTable.into());
+    }
+
+    let mut ancestor_level = 0;
+    let mut external_refs = Vec::new();
+    let mut folder_depth = 0;
+    if num_external_nodes > 0 {
+        ancestor_level = cursor.read_u32().context("Reading ancestor level")?;
+        let mut folders = Vec::new();
+        folder_depth = read_ref_table_folders(cursor, &mut folders).context("Reading folders")?;
+
+        for i in 0..num_external_nodes {
+            let flags = cursor
+                .read_u32()
+                .with_context(|| format!("Reading external node {i} flags"))?;
+            let mut file_name = None;
+            let mut resource_index = None;
+            if (flags & 4) == 0 {
+                file_name = Some(
+                    cursor
+                        .read_string()
+                        .with_context(|| format!("Reading external node {i} file name"))?
+                        .to_owned(),
+                );
+            } else {
+                resource_index = Some(
+                    cursor
+                        .read_u32()
+                        .with_context(|| format!("Reading external node {i} resource index"))?,
+                );
+            }
+            let node_index = cursor
+                .read_u32()
+                .with_context(|| format!("Reading external node {i} index"))?;
+            let mut use_file = false;
+            if version >= 5 {
+                use_file = cursor
+                    .read_bool()
+                    .with_context(|| format!("Reading external node {i} use file"))?;
+            }
+            let mut folder = None;
+            if (flags & 4) == 0 {
+                let folder_index = cursor
+                    .read_u32()
+                    .with_context(|| format!("Reading external node {i} folder index"))?;
+                // 0 is the ancestor folder itself
+                folder = match folder_index.checked_sub(1) {
+                    Some(index) => Some(
+                        folders
+                            .get(index as usize)
+                            .cloned()
+                            .ok_or(GbxErrorInner::InvalidFolderIndex(folder_index))?,
+                    ),
+                    None => Some(String::new()),
+                };
+            }
+            external_refs.push(ExternalRef {
+                node_index,
+                flags,
+                file_name,
+                resource_index,
+                folder,
+                use_file,
+            });
+        }
+    }
+
     Ok(Header {
         version,
         byte_format,
+        ref_table_compression,
         body_compression,
+        unknown_flag,
         class_id,
         chunks,
         num_nodes,
         num_external_nodes,
+        ancestor_level,
+        external_refs,
+        folder_depth,
     })
 }
 
+/// Reads the reference table's folder tree into `folders`, as paths in the order external nodes
+/// refer to them by. Returns how deeply the folders are nested.
+fn read_ref_table_folders(
+    cursor: &mut BodyCursor,
+    folders: &mut Vec<String>,
+) -> Result<usize, GbxError> {
+    // the path of each folder whose subfolders are being read, and how many of them are left,
+    // kept on a stack so a deeply nested tree can't overflow the call stack
+    let num_folders = cursor.read_u32().context("Reading number of folders")?;
+    let mut open = vec![(String::new(), num_folders)];
+    let mut depth = 0;
+    while let Some((parent, remaining)) = open.last_mut() {
+        if *remaining == 0 {
+            open.pop();
+            continue;
+        }
+        *remaining -= 1;
+
+        let i = folders.len();
+        let name = cursor
+            .read_string()
+            .with_context(|| format!("Reading folder {i} name"))?;
+        let path = if parent.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{parent}/{name}")
+        };
+        let num_folders = cursor
+            .read_u32()
+            .with_context(|| format!("Reading folder {i} number of folders"))?;
+        folders.push(path.clone());
+        open.push((path, num_folders));
+        depth = depth.max(open.len() - 1);
+    }
+    Ok(depth)
+}
+
+/// Copies a GBX file from `reader` to `data` up to where its body starts, reading nothing past
+/// it. Returns the size of the body as stored, if it is compressed.
+fn read_header_data<R: BufRead>(
+    reader: &mut R,
+    data: &mut Vec<u8>,
+) -> Result<Option<u32>, GbxError> {
+    let mut stream = HeaderStream {
+        reader,
+        data,
+        text: false,
+    };
+
+    // magic and version
+    stream.bytes(5)?;
+    if stream.data[..3] != *b"GBX" {
+        return Err(GbxErrorInner::NotGbx.into());
+    }
+    let version = u16::from_le_bytes([stream.data[3], stream.data[4]]);
+
+    // format flags, after which everything is written in the byte format
+    stream.bytes(if version >= 4 { 4 } else { 3 })?;
+    stream.text = stream.data[5] == u8::from(ByteFormat::Text);
+    let ref_table_compressed = stream.data[6] == u8::from(Compression::Compressed);
+    let body_compressed = stream.data[7] == u8::from(Compression::Compressed);
+
+    // class ID, and user data size
+    stream.u32()?;
+    if version >= 6 {
+        stream.u32()?;
+    }
+
+    let num_header_chunks = stream.u32()?;
+    let mut header_chunks_size = 0;
+    for _ in 0..num_header_chunks {
+        // ID, and size with the heavy flag
+        stream.u32()?;
+        header_chunks_size += (stream.u32()? & !0x8000_0000) as u64;
+    }
+    stream.bytes(header_chunks_size)?;
+
+    // number of nodes and external nodes
+    stream.u32()?;
+    let num_external_nodes = stream.u32()?;
+    if num_external_nodes > 0 {
+        if ref_table_compressed {
+            return Err(GbxErrorInner::CompressedRefTable.into());
+        }
+
+        // ancestor level
+        stream.u32()?;
+
+        // each folder's name and number of subfolders, followed by the subfolders. Only how many
+        // are left at each level is kept, as in `read_ref_table_folders`.
+        let mut open = vec![stream.u32()?];
+        while let Some(remaining) = open.last_mut() {
+            if *remaining == 0 {
+                open.pop();
+                continue;
+            }
+            *remaining -= 1;
+            stream.string()?;
+            open.push(stream.u32()?);
+        }
+
+        for _ in 0..num_external_nodes {
+            let flags = stream.u32()?;
+            // file name, or resource index
+            if (flags & 4) == 0 {
+                stream.string()?;
+            } else {
+                stream.u32()?;
+            }
+            // node index
+            stream.u32()?;
+            if version >= 5 {
+                // use file
+                stream.u32()?;
+            }
+            if (flags & 4) == 0 {
+                // folder index
+                stream.u32()?;
+            }
+        }
+    }
+
+    if !body_compressed {
+        return Ok(None);
+    }
+    // uncompressed and compressed size
+    stream.u32()?;
+    Ok(Some(stream.u32()?))
+}
+
+/// Reads values from the header of a file as it is streamed, appending the data they are
+/// stored as to `data`, the same way [`BodyCursor`] reads them
+struct HeaderStream<'a, R> {
+    reader: &'a mut R,
+    data: &'a mut Vec<u8>,
+    text: bool,
+}
+
+impl<R: BufRead> HeaderStream<'_, R> {
+    fn bytes(&mut self, count: u64) -> Result<(), GbxError> {
+        read_data(self.reader, self.data, count)
+    }
+
+    fn u32(&mut self) -> Result<u32, GbxError> {
+        if !self.text {
+            self.bytes(4)?;
+            return Ok(data_u32(self.data, self.data.len() - 4));
+        }
+
+        self.copy_while(|byte| byte.is_ascii_whitespace())?;
+        let position = self.data.len();
+        self.copy_while(|byte| !byte.is_ascii_whitespace())?;
+        std::str::from_utf8(&self.data[position..])
+            .ok()
+            .and_then(|token| token.parse::<i128>().ok())
+            .and_then(|value| u32::try_from(value).ok())
+            .ok_or_else(|| GbxErrorInner::InvalidText { position }.into())
+    }
+
+    fn string(&mut self) -> Result<(), GbxError> {
+        let count = self.u32()?;
+        if self.text {
+            // a single separator, which may be a line break
+            if self.copy_if(|byte| byte.is_ascii_whitespace())? == Some(b'\r') {
+                self.copy_if(|byte| byte == b'\n')?;
+            }
+        }
+        self.bytes(count as u64)
+    }
+
+    /// Copies bytes for as long as they match `predicate`
+    fn copy_while(&mut self, predicate: impl Fn(u8) -> bool) -> Result<(), GbxError> {
+        loop {
+            let buffer = self.reader.fill_buf()?;
+            let count = buffer.iter().take_while(|byte| predicate(**byte)).count();
+            let done = count < buffer.len() || buffer.is_empty();
+            self.data.extend_from_slice(&buffer[..count]);
+            self.reader.consume(count);
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Copies the next byte if it matches `predicate`
+    fn copy_if(&mut self, predicate: impl Fn(u8) -> bool) -> Result<Option<u8>, GbxError> {
+        let Some(&byte) = self.reader.fill_buf()?.first() else {
+            return Ok(None);
+        };
+        if !predicate(byte) {
+            return Ok(None);
+        }
+        self.data.push(byte);
+        self.reader.consume(1);
+        Ok(Some(byte))
+    }
+}
+
+/// Appends exactly `count` bytes from `reader` to `data`
+fn read_data<R: Read>(reader: &mut R, data: &mut Vec<u8>, count: u64) -> Result<(), GbxError> {
+    // not reserved up front, so a corrupt size can't allocate more than the file has
+    let read = reader.take(count).read_to_end(data)?;
+    if (read as u64) < count {
+        return Err(GbxErrorInner::Io(std::io::ErrorKind::UnexpectedEof.into()).into());
+    }
+    Ok(())
+}
+
+/// Decompresses a body of `uncompressed_size` bytes, according to the file
+fn decompress_body<R: BufRead>(
+    reader: R,
+    uncompressed_size: u32,
+    limits: &ParseLimits,
+) -> Result<Vec<u8>, GbxError> {
+    let limit = limits.max_decompressed_size;
+    if uncompressed_size as usize > limit {
+        return Err(GbxErrorInner::DecompressedSizeLimit {
+            size: uncompressed_size as usize,
+            limit,
+        }
+        .into());
+    }
+
+    // the size in the file doesn't have to match what the data decompresses to, so the limit is
+    // enforced while decompressing too
+    lzo::decompress(reader, uncompressed_size as usize, limit).context("Decompressing body")
+}
+
+fn data_u32(data: &[u8], start: usize) -> u32 {
+    u32::from_le_bytes([
+        data[start],
+        data[start + 1],
+        data[start + 2],
+        data[start + 3],
+    ])
+}
+
 pub struct Node<'data> {
     header: Header,
-    data: &'data [u8],
-    body: Vec<u8>,
+    /// The whole file, or only the header for files read with [`Node::read_stream`]
+    data: Cow<'data, [u8]>,
+    /// Where the body is in `data`, as stored in the file before decompression
+    raw_body: Option<Range<usize>>,
+    /// Size of the decompressed body, according to the file
+    uncompressed_size: u32,
+    /// Decompressed on first use
+    body: OnceLock<Vec<u8>>,
+    registry: Option<Arc<registry::ChunkRegistry>>,
+    limits: ParseLimits,
 }
 
 impl Debug for Node<'_> {
@@ -321,58 +935,512 @@ impl Debug for Node<'_> {
 
 impl<'data> Node<'data> {
     pub fn read_from<B: AsRef<[u8]> + 'data>(data: &'data B) -> Result<Node<'data>, GbxError> {
-        let data = data.as_ref();
-        let mut cursor = Cursor::new(data);
-        let header = parse_header(&mut cursor).context("Parsing header")?;
-        if header.body_compression != Compression::Compressed {
-            return Err(GbxErrorInner::NotCompressed.into());
-        }
-
-        let uncompressed_size = cursor
-            .read_u32::<LE>()
-            .context("Reading uncompressed size")?;
-        tracing::debug!("uncompressed size {}", uncompressed_size);
-        let compressed_size = cursor.read_u32::<LE>().context("Reading compressed size")?;
-        tracing::debug!("compressed size {}", compressed_size);
-
-        #[allow(unexpected_cfgs)]
-        let body = if cfg!(fuzzing) {
-            let position = cursor.position() as usize;
-            let inner = cursor.into_inner();
-            Ok(Vec::from(&inner.as_ref()[position..]))
-        } else {
-            lzokay_native::decompress(&mut cursor, None).context("Decompressing body")
+        Node::read_from_with_limits(data, ParseLimits::default())
+    }
+
+    /// Like [`Node::read_from`], but the header is read and the body decompressed within
+    /// `limits`, which the node then parses within too
+    pub fn read_from_with_limits<B: AsRef<[u8]> + 'data>(
+        data: &'data B,
+        limits: ParseLimits,
+    ) -> Result<Node<'data>, GbxError> {
+        let node = Node::from_data(Cow::Borrowed(data.as_ref()), limits)?;
+        node.body()?;
+        Ok(node)
+    }
+
+    /// Reads only the header. The body is decompressed when it is first needed, so this is
+    /// much cheaper than [`Node::read_from`] when only header chunks are of interest.
+    pub fn read_header<B: AsRef<[u8]> + 'data>(data: &'data B) -> Result<Node<'data>, GbxError> {
+        Node::from_data(Cow::Borrowed(data.as_ref()), ParseLimits::default())
+    }
+
+    /// Reads a file from a stream, such as an upload that is still arriving, without needing all
+    /// of it in memory first. The header is read value by value up to where the body starts, in
+    /// text files and through reference tables too, and the body is decompressed straight from
+    /// `reader` into a buffer the node owns. `reader` is buffered, so it doesn't need to be.
+    ///
+    /// The compressed body is not kept, so [`Node::write_challenge`] compresses it again.
+    pub fn read_stream<R: Read>(reader: R) -> Result<Node<'static>, GbxError> {
+        Node::read_stream_with_limits(reader, ParseLimits::default())
+    }
+
+    /// Like [`Node::read_stream`], but the body is read and decompressed within `limits`, which
+    /// the node then parses within too. Bodies that aren't compressed can't be larger than
+    /// [`ParseLimits::max_decompressed_size`] either.
+    pub fn read_stream_with_limits<R: Read>(
+        reader: R,
+        limits: ParseLimits,
+    ) -> Result<Node<'static>, GbxError> {
+        let mut reader = BufReader::new(reader);
+        let mut data = Vec::new();
+        let compressed_size =
+            read_header_data(&mut reader, &mut data).context("Streaming header")?;
+
+        let mut node = Node::from_data(Cow::Owned(data), limits)?;
+        let body = match compressed_size {
+            Some(compressed_size) => decompress_body(
+                reader.take(compressed_size as u64),
+                node.uncompressed_size,
+                &node.limits,
+            )?,
+            None => {
+                // one byte more than the limit, to tell a body at the limit from a larger one
+                let limit = node.limits.max_decompressed_size;
+                let mut body = Vec::new();
+                reader
+                    .take(limit as u64 + 1)
+                    .read_to_end(&mut body)
+                    .context("Streaming body")?;
+                if body.len() > limit {
+                    return Err(GbxErrorInner::DecompressedSizeLimit {
+                        size: body.len(),
+                        limit,
+                    }
+                    .into());
+                }
+                body
+            }
+        };
+
+        node.raw_body = None;
+        node.body = OnceLock::from(body);
+        Ok(node)
+    }
+
+    fn from_data(data: Cow<'data, [u8]>, limits: ParseLimits) -> Result<Node<'data>, GbxError> {
+        let mut cursor = BodyCursor::new_header(Cursor::new(&*data)).with_limits(limits);
+        let header = parse_header(&mut cursor)
+            .map_err(|err| err.with_location(|| cursor.location()))
+            .context("Parsing header")?;
+
+        let mut uncompressed_size = 0;
+        let raw_body = match header.body_compression {
+            Compression::Compressed => {
+                uncompressed_size = cursor.read_u32().context("Reading uncompressed size")?;
+                tracing::debug!("uncompressed size {}", uncompressed_size);
+                let compressed_size = cursor.read_u32().context("Reading compressed size")?;
+                tracing::debug!("compressed size {}", compressed_size);
+
+                let position = cursor.position() as usize;
+                position..data.len().min(position + compressed_size as usize)
+            }
+
+            Compression::Uncompressed => cursor.position() as usize..data.len(),
+        };
+
+        Ok(Node {
+            header,
+            data,
+            raw_body: Some(raw_body),
+            uncompressed_size,
+            body: OnceLock::new(),
+            registry: None,
+            limits,
+        })
+    }
+
+    /// The body as stored in the file, if it was kept
+    fn raw_body(&self) -> Option<&[u8]> {
+        self.raw_body.clone().map(|raw_body| &self.data[raw_body])
+    }
+
+    /// Parses with the handlers in `registry` as well as the built-in ones
+    pub fn with_registry(self, registry: impl Into<Arc<registry::ChunkRegistry>>) -> Self {
+        Node {
+            registry: Some(registry.into()),
+            ..self
+        }
+    }
+
+    /// Parses within `limits` instead of the defaults. The body is decompressed within them too
+    /// if it hasn't been yet, which is only the case for nodes from [`Node::read_header`]. To
+    /// decompress the body of other nodes within them, use [`Node::read_from_with_limits`] or
+    /// [`Node::read_stream_with_limits`].
+    pub fn with_limits(self, limits: ParseLimits) -> Self {
+        Node { limits, ..self }
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// The data of a header chunk, as stored in the file
+    pub fn header_chunk_data(&self, chunk: &HeaderChunk) -> Option<&[u8]> {
+        let start = chunk.data_start as usize;
+        self.data.get(start..start + chunk.size as usize)
+    }
+
+    /// The decompressed body
+    pub fn body(&self) -> Result<&[u8], GbxError> {
+        if let Some(body) = self.body.get() {
+            return Ok(body);
+        }
+
+        let body = match self.header.body_compression {
+            #[allow(unexpected_cfgs)]
+            Compression::Compressed if !cfg!(fuzzing) => decompress_body(
+                self.raw_body().unwrap_or_default(),
+                self.uncompressed_size,
+                &self.limits,
+            )?,
+            _ => Vec::from(self.raw_body().unwrap_or_default()),
         };
 
-        let body = body?;
-        Ok(Node { header, data, body })
+        Ok(self.body.get_or_init(|| body))
+    }
+
+    pub fn byte_format(&self) -> ByteFormat {
+        self.header.byte_format
     }
 
     pub fn parse(&self) -> Result<parse::CGame, GbxError> {
-        let mut this = parse::CGame::parse(
-            &mut BodyCursor::new(Cursor::new(&self.body)),
-            self.header.class_id,
+        self.parse_recorded(None).map(|(this, _report)| this)
+    }
+
+    /// Parses the node, also returning which chunks were skipped and which fields weren't found
+    pub fn parse_with_report(&self) -> Result<(parse::CGame<'_>, ParseReport), GbxError> {
+        self.parse_recorded(None)
+    }
+
+    /// The top-level chunks of the body, in file order. The body has to be parsed to find them.
+    pub fn body_chunks(&self) -> Result<Vec<BodyChunk>, GbxError> {
+        let mut recorded = parse::RecordedChunks::default();
+        self.parse_recorded(Some(&mut recorded))?;
+        Ok(recorded
+            .body
+            .iter()
+            .map(|chunk| BodyChunk {
+                id: chunk.id,
+                size: chunk.payload().len() as u32,
+                skippable: chunk.is_skippable(),
+            })
+            .collect())
+    }
+
+    /// Parses only the header chunks, without decompressing the body
+    pub fn parse_header(&self) -> Result<parse::CGame<'_>, GbxError> {
+        self.check_folder_depth()?;
+        let mut this = parse::CGame::empty(self.header.class_id, self.registry.as_deref())?;
+        self.parse_header_chunks(&mut this, None, Rc::default())?;
+        Ok(this)
+    }
+
+    /// Writes `challenge` back into a GBX file using this node's header and chunk layout, so
+    /// `challenge` has to come from parsing this node. Chunks the writer does not model are
+    /// copied from the original file unchanged.
+    ///
+    /// The fields that can be edited are the map's name, info, kind, decoration and size, its
+    /// medal times, cost, lap and checkpoint counts, play mode, editor mode flags, password, map
+    /// coordinates, pack mask, type and style, lightmap version, title ID, header version, XML
+    /// data, thumbnail and author information, as long as the original file stores them. Changes
+    /// that can't be written fail instead of being silently dropped, which takes parsing the
+    /// written file.
+    pub fn write_challenge<'node>(
+        &'node self,
+        challenge: &parse::CtnChallenge<'node>,
+    ) -> Result<Vec<u8>, GbxError> {
+        write::write_challenge(self, challenge)
+    }
+
+    /// Parses the node, optionally recording the raw bytes of every header and body chunk
+    pub(crate) fn parse_recorded<'node>(
+        &'node self,
+        mut recorded: Option<&mut parse::RecordedChunks<'node>>,
+    ) -> Result<(parse::CGame<'node>, ParseReport), GbxError> {
+        self.check_folder_depth()?;
+        let report = Rc::<RefCell<ParseReport>>::default();
+        let mut body_cursor = BodyCursor::new(Cursor::new(self.body()?))
+            .with_byte_format(self.header.byte_format)
+            .with_report(report.clone())
+            .with_registry(self.registry.clone())
+            .with_limits(self.limits)
+            .with_external_refs(&self.header.external_refs);
+        let mut this = match recorded.as_deref_mut() {
+            Some(recorded) => parse::CGame::parse_recorded(
+                &mut body_cursor,
+                self.header.class_id,
+                &mut recorded.body,
+            ),
+            None => parse::CGame::parse(&mut body_cursor, self.header.class_id),
+        }
+        .map_err(|err| err.with_location(|| body_cursor.location()))?;
+
+        self.parse_header_chunks(
+            &mut this,
+            recorded.map(|recorded| &mut recorded.header),
+            report.clone(),
         )?;
 
-        let mut cursor = BodyCursor::new(Cursor::new(self.data));
+        let mut report = report.take();
+        report.missing_fields = this
+            .missing_fields()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        Ok((this, report))
+    }
+
+    /// The reference table is read before limits can be set, so its folders are only checked
+    /// against them once the node is parsed
+    fn check_folder_depth(&self) -> Result<(), GbxError> {
+        let max_depth = self.limits.max_depth;
+        if self.header.folder_depth > max_depth {
+            return Err(GbxErrorInner::DepthLimit(max_depth))
+                .context("Checking reference table folders");
+        }
+        Ok(())
+    }
+
+    fn parse_header_chunks<'node>(
+        &'node self,
+        this: &mut parse::CGame<'node>,
+        mut recorded: Option<&mut Vec<parse::RawChunk<'node>>>,
+        report: Rc<RefCell<ParseReport>>,
+    ) -> Result<(), GbxError> {
+        let mut cursor = BodyCursor::new_header(Cursor::new(&*self.data))
+            .with_byte_format(self.header.byte_format)
+            .with_report(report)
+            .with_registry(self.registry.clone())
+            .with_limits(self.limits);
         for header_chunk in self.header.chunks.iter() {
-            tracing::trace!("Parsing header chunk {:08x}", header_chunk.id);
-            cursor.set_position(header_chunk.data_start);
-            this.parse_one(&mut cursor, self.header.class_id | header_chunk.id)?;
+            let lookback_before = cursor.lookback().clone();
+
+            // legacy class IDs are mapped to current ones, like in the body
+            let chunk_id = header_chunk.full_id;
+            let wrapped_chunk_id = parse::wrap_chunk_id(chunk_id);
+            let registered = self
+                .registry
+                .as_ref()
+                .is_some_and(|registry| registry.handler(wrapped_chunk_id).is_some());
+            if registered || parse::CGame::supports(wrapped_chunk_id) {
+                tracing::trace!("Parsing header chunk {:08x}", chunk_id);
+                cursor.set_position(header_chunk.data_start);
+                cursor.set_chunk_end(Some(header_chunk.data_start + header_chunk.size as u64));
+                this.parse_one(&mut cursor, chunk_id)?;
+            } else {
+                tracing::warn!(
+                    "Unsupported: header chunk {:08x} for class {:08x}",
+                    chunk_id,
+                    self.header.class_id
+                );
+                cursor.report().skipped_chunks.push(SkippedChunk {
+                    chunk_id,
+                    class_id: self.header.class_id,
+                    size: header_chunk.size,
+                    offset: header_chunk.data_start,
+                    header: true,
+                });
+            }
+
+            if let Some(recorded) = recorded.as_deref_mut() {
+                let data = self.header_chunk_data(header_chunk).ok_or_else(|| {
+                    let start = header_chunk.data_start as usize;
+                    GbxErrorInner::OutOfBounds {
+                        start,
+                        end: start + header_chunk.size as usize,
+                    }
+                })?;
+                recorded.push(parse::RawChunk {
+                    id: chunk_id,
+                    heavy: header_chunk.heavy,
+                    data,
+                    lookback_before,
+                    lookback_after: cursor.lookback().clone(),
+                });
+            }
         }
 
-        Ok(this)
+        Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
+/// Converts parsed data that borrows from the file into data that owns everything
+pub(crate) trait IntoOwned {
+    type Owned: 'static;
+
+    fn into_owned(self) -> Self::Owned;
+}
+
+macro_rules! into_owned_identity {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl IntoOwned for $ty {
+                type Owned = $ty;
+
+                fn into_owned(self) -> Self::Owned {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+into_owned_identity!(
+    bool,
+    u8,
+    u16,
+    u32,
+    i32,
+    u64,
+    u128,
+    f32,
+    [u8; 3],
+    [u8; 32],
+    [u32; 3],
+    [f32; 2],
+    [f32; 3],
+    GhostCheckpoint,
+    MediaTrigger,
+    SimiKey,
+    MapKind,
+    PlayMode,
+    EditorMode,
+    Direction,
+    DifficultyColor,
+    PhaseOffset,
+    ItemType,
+    WaypointType,
+    ItemModelKind,
+    ExternalRef,
+);
+
+impl IntoOwned for Cow<'_, str> {
+    type Owned = Cow<'static, str>;
+
+    fn into_owned(self) -> Cow<'static, str> {
+        Cow::Owned(Cow::into_owned(self))
+    }
+}
+
+impl IntoOwned for Cow<'_, [u8]> {
+    type Owned = Cow<'static, [u8]>;
+
+    fn into_owned(self) -> Cow<'static, [u8]> {
+        Cow::Owned(Cow::into_owned(self))
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Option<T> {
+    type Owned = Option<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.map(T::into_owned)
+    }
+}
+
+/// Only copies the node if something else still shares it
+impl<T: IntoOwned + Clone> IntoOwned for Arc<T> {
+    type Owned = Arc<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        Arc::new(Arc::unwrap_or_clone(self).into_owned())
+    }
+}
+
+impl<T: IntoOwned + Clone> IntoOwned for NodeRef<T> {
+    type Owned = NodeRef<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            NodeRef::Local(node) => NodeRef::Local(node.into_owned()),
+            NodeRef::External(external_ref) => NodeRef::External(external_ref),
+        }
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(T::into_owned).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Meta<'node> {
-    pub id: &'node str,
-    pub collection: &'node str,
-    pub author: &'node str,
+    pub id: Cow<'node, str>,
+    pub collection: Cow<'node, str>,
+    pub author: Cow<'node, str>,
+}
+
+impl IntoOwned for Meta<'_> {
+    type Owned = Meta<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Meta {
+            id: IntoOwned::into_owned(self.id),
+            collection: IntoOwned::into_owned(self.collection),
+            author: IntoOwned::into_owned(self.author),
+        }
+    }
+}
+
+/// A reference to a file outside the GBX, like a skin or a mod pack
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FileRef<'node> {
+    pub path: Cow<'node, str>,
+    pub checksum: Option<[u8; 32]>,
+    pub locator_url: Option<Cow<'node, str>>,
 }
 
-#[derive(Debug, Clone, strum::FromRepr, PartialEq)]
+impl IntoOwned for FileRef<'_> {
+    type Owned = FileRef<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        FileRef {
+            path: IntoOwned::into_owned(self.path),
+            checksum: self.checksum,
+            locator_url: IntoOwned::into_owned(self.locator_url),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GhostCheckpoint {
+    pub time: Option<i32>,
+    pub stunt_score: i32,
+}
+
+/// Where a MediaTracker clip in a clip group is triggered, and under which condition
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MediaTrigger {
+    pub coords: Vec<[u32; 3]>,
+    pub condition: u32,
+    pub condition_value: f32,
+}
+
+/// A keyframe of a 2D MediaTracker effect, like the placement of a text or image
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SimiKey {
+    pub time: f32,
+    pub position: [f32; 2],
+    pub rotation: f32,
+    pub scale: [f32; 2],
+    pub opacity: f32,
+    pub depth: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GhostInput<'node> {
+    pub time: u32,
+    pub name: Cow<'node, str>,
+    pub data: u32,
+}
+
+impl IntoOwned for GhostInput<'_> {
+    type Owned = GhostInput<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        GhostInput {
+            time: self.time,
+            name: IntoOwned::into_owned(self.name),
+            data: self.data,
+        }
+    }
+}
+
+#[derive(Debug, Clone, strum::FromRepr, PartialEq, serde::Serialize, serde::Deserialize)]
 #[repr(u8)]
 pub enum MapKind {
     EndMarker = 0,
@@ -389,3 +1457,122 @@ pub enum MapKind {
     SoloNadeo = 11,
     MultiNadeo = 12,
 }
+
+#[derive(Debug, Clone, Copy, strum::FromRepr, PartialEq, serde::Serialize, serde::Deserialize)]
+#[repr(u8)]
+pub enum PlayMode {
+    Race = 0,
+    Platform = 1,
+    Puzzle = 2,
+    Crazy = 3,
+    Shortcut = 4,
+    Stunts = 5,
+    Script = 6,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum EditorMode {
+    Advanced,
+    Simple,
+}
+
+// Flags stored together with the editor mode
+pub(crate) const EDITOR_MODE_SIMPLE: u32 = 1;
+pub(crate) const EDITOR_MODE_GHOST_BLOCKS: u32 = 2;
+pub(crate) const EDITOR_MODE_GAMEPAD: u32 = 4;
+
+#[derive(Debug, Clone, Copy, strum::FromRepr, PartialEq, serde::Serialize, serde::Deserialize)]
+#[repr(u8)]
+pub enum Direction {
+    North = 0,
+    East = 1,
+    South = 2,
+    West = 3,
+}
+
+#[derive(Debug, Clone, Copy, strum::FromRepr, PartialEq, serde::Serialize, serde::Deserialize)]
+#[repr(u8)]
+pub enum DifficultyColor {
+    Default = 0,
+    White = 1,
+    Green = 2,
+    Blue = 3,
+    Red = 4,
+    Black = 5,
+}
+
+#[derive(Debug, Clone, Copy, strum::FromRepr, PartialEq, serde::Serialize, serde::Deserialize)]
+#[repr(u8)]
+pub enum PhaseOffset {
+    None = 0,
+    One8th = 1,
+    Two8th = 2,
+    Three8th = 3,
+    Four8th = 4,
+    Five8th = 5,
+    Six8th = 6,
+    Seven8th = 7,
+}
+
+#[derive(Debug, Clone, Copy, strum::FromRepr, PartialEq, serde::Serialize, serde::Deserialize)]
+#[repr(u8)]
+pub enum ItemType {
+    Undefined = 0,
+    Ornament = 1,
+    PickUp = 2,
+    Character = 3,
+    Vehicle = 4,
+    Spot = 5,
+    Cannon = 6,
+    Group = 7,
+    Decal = 8,
+    Turret = 9,
+    Wagon = 10,
+    Block = 11,
+    EntitySpawner = 12,
+    DeprecV = 13,
+    Procedural = 14,
+    Generic = 15,
+}
+
+#[derive(Debug, Clone, Copy, strum::FromRepr, PartialEq, serde::Serialize, serde::Deserialize)]
+#[repr(u8)]
+pub enum WaypointType {
+    Start = 0,
+    Finish = 1,
+    Checkpoint = 2,
+    None = 3,
+    StartFinish = 4,
+    Dispenser = 5,
+}
+
+/// Whether an item was made from blocks in the editor, or imported as a mesh
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum ItemModelKind {
+    Block,
+    Mesh,
+}
+
+/// The icon of an item or macroblock, from its header
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ItemIcon<'node> {
+    pub width: u16,
+    pub height: u16,
+    /// Whether `data` is a WebP image, rather than `width * height` RGBA pixels
+    pub webp: bool,
+    #[serde(with = "base64_bytes")]
+    pub data: Cow<'node, [u8]>,
+}
+
+impl IntoOwned for ItemIcon<'_> {
+    type Owned = ItemIcon<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        ItemIcon {
+            width: self.width,
+            height: self.height,
+            webp: self.webp,
+            data: IntoOwned::into_owned(self.data),
+        }
+    }
+}
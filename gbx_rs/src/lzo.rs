@@ -0,0 +1,173 @@
+//! LZO1X decompression for GBX bodies. Unlike `lzokay_native::decompress`, malformed data fails
+//! with an error instead of panicking, and the output can't grow past a limit, however much the
+//! data claims to decompress to.
+
+use crate::{GbxError, GbxErrorInner};
+use std::io::BufRead;
+
+/// Most of the output to allocate up front. The size a file claims can't be trusted, so only
+/// this much is, and anything larger grows as it is decompressed.
+const MAX_PREALLOCATION: usize = 1 << 20;
+
+/// Decompresses all of `reader` into at most `limit` bytes. `expected_size` is only used to
+/// allocate the output up front, up to [`MAX_PREALLOCATION`].
+pub(crate) fn decompress<R: BufRead>(
+    reader: R,
+    expected_size: usize,
+    limit: usize,
+) -> Result<Vec<u8>, GbxError> {
+    let mut input = Input { reader };
+    let mut output = Output {
+        data: Vec::with_capacity(expected_size.min(limit).min(MAX_PREALLOCATION)),
+        limit,
+    };
+
+    // the first byte may start with a literal run, instead of an instruction
+    let mut state = 0;
+    match input.peek()? {
+        first @ 22.. => {
+            input.byte()?;
+            output.literal(&mut input, first as usize - 17)?;
+            state = 4;
+        }
+        first @ 18..=21 => {
+            input.byte()?;
+            state = first as usize - 17;
+            output.literal(&mut input, state)?;
+        }
+        _ => {}
+    }
+
+    loop {
+        let instruction = input.byte()? as usize;
+        let (distance, length, next_state) = if instruction & 0xc0 != 0 {
+            // M2: 3 to 8 bytes from within 2 kB
+            let distance = (input.byte()? as usize) << 3 | (instruction >> 2 & 0x7);
+            (distance + 1, (instruction >> 5) + 1, instruction & 0x3)
+        } else if instruction & 0x20 != 0 {
+            // M3: any length from within 16 kB
+            let mut length = (instruction & 0x1f) + 2;
+            if length == 2 {
+                length += input.length_run()? + 31;
+            }
+            let value = input.u16()? as usize;
+            ((value >> 2) + 1, length, value & 0x3)
+        } else if instruction & 0x10 != 0 {
+            // M4: any length from 16 to 48 kB back, or the end of the stream
+            let mut length = (instruction & 0x7) + 2;
+            if length == 2 {
+                length += input.length_run()? + 7;
+            }
+            let value = input.u16()? as usize;
+            let distance = ((instruction & 0x8) << 11) + (value >> 2);
+            if distance == 0 {
+                // the end marker is always encoded with a length of 3
+                if length != 3 {
+                    return Err(GbxErrorInner::InvalidCompressedData.into());
+                }
+                break;
+            }
+            (distance + 0x4000, length, value & 0x3)
+        } else if state == 0 {
+            // M1 after no literals: a literal run of at least 4 bytes
+            let mut length = instruction + 3;
+            if length == 3 {
+                length += input.length_run()? + 15;
+            }
+            output.literal(&mut input, length)?;
+            state = 4;
+            continue;
+        } else if state != 4 {
+            // M1 after 1 to 3 literals: 2 bytes from within 1 kB
+            let distance = (input.byte()? as usize) << 2 | (instruction >> 2);
+            (distance + 1, 2, instruction & 0x3)
+        } else {
+            // M1 after a literal run: 3 bytes from 2 to 3 kB back
+            let distance = (input.byte()? as usize) << 2 | (instruction >> 2);
+            (distance + 2049, 3, instruction & 0x3)
+        };
+
+        output.copy_back(distance, length)?;
+        state = next_state;
+        output.literal(&mut input, state)?;
+    }
+
+    Ok(output.data)
+}
+
+struct Input<R> {
+    reader: R,
+}
+
+impl<R: BufRead> Input<R> {
+    fn peek(&mut self) -> Result<u8, GbxError> {
+        match self.reader.fill_buf()?.first() {
+            Some(byte) => Ok(*byte),
+            None => Err(GbxErrorInner::Io(std::io::ErrorKind::UnexpectedEof.into()).into()),
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, GbxError> {
+        let mut byte = [0];
+        self.reader.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, GbxError> {
+        let mut bytes = [0; 2];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(u16::from_le_bytes(bytes))
+    }
+
+    /// Long lengths are stored as a run of zero bytes, each worth 255, and a final non-zero byte
+    fn length_run(&mut self) -> Result<usize, GbxError> {
+        let mut length = 0usize;
+        loop {
+            match self.byte()? {
+                0 => length = length.saturating_add(255),
+                byte => return Ok(length.saturating_add(byte as usize)),
+            }
+        }
+    }
+}
+
+struct Output {
+    data: Vec<u8>,
+    limit: usize,
+}
+
+impl Output {
+    fn reserve(&mut self, length: usize) -> Result<(), GbxError> {
+        let size = self.data.len().saturating_add(length);
+        if size > self.limit {
+            return Err(GbxErrorInner::DecompressedSizeLimit {
+                size,
+                limit: self.limit,
+            }
+            .into());
+        }
+        self.data.reserve(length);
+        Ok(())
+    }
+
+    fn literal<R: BufRead>(&mut self, input: &mut Input<R>, length: usize) -> Result<(), GbxError> {
+        self.reserve(length)?;
+        let start = self.data.len();
+        self.data.resize(start + length, 0);
+        input.reader.read_exact(&mut self.data[start..])?;
+        Ok(())
+    }
+
+    /// Copies `length` bytes starting `distance` bytes back, which may overlap what is copied
+    fn copy_back(&mut self, distance: usize, length: usize) -> Result<(), GbxError> {
+        if distance > self.data.len() {
+            return Err(GbxErrorInner::InvalidCompressedData.into());
+        }
+        self.reserve(length)?;
+        let start = self.data.len() - distance;
+        for i in start..start + length {
+            self.data.push(self.data[i]);
+        }
+        Ok(())
+    }
+}
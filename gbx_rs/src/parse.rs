@@ -1,24 +1,73 @@
 use crate::{
-    cursor::{BodyCursor, CursorExt},
-    Context, GbxError, GbxErrorInner, MapKind, Meta,
+    cursor::{BodyCursor, Lookback, SKIP},
+    registry::{ChunkReader, ChunkRegistry, ExtensionNode, Extensions},
+    xml::{self, HeaderXml},
+    Context, DifficultyColor, Direction, EditorMode, ExternalRef, FileRef, GbxError, GbxErrorInner,
+    GhostCheckpoint, GhostInput, IntoOwned, ItemIcon, ItemModelKind, ItemType, MapKind,
+    MediaTrigger, Meta, Node, NodeRef, ParseLimits, PhaseOffset, PlayMode, SimiKey, SkippedChunk,
+    WaypointType, EDITOR_MODE_GAMEPAD, EDITOR_MODE_GHOST_BLOCKS, EDITOR_MODE_SIMPLE,
+};
+use std::{
+    borrow::Cow,
+    io::{Cursor, Read, Seek},
+    sync::Arc,
 };
-use byteorder::{ReadBytesExt, LE};
-use std::io::Seek;
 
 macro_rules! parser {
     (
         $(
             $class_id:literal $variant:ident {
-                $( $(#[ $ignore:meta ])? $prop:ident : $ty:ty ),* $(,)?
+                $( $(#[ $attr:meta ])* $prop:ident : $ty:ty ),* $(,)?
             } {
                 $( $chunk_id:literal => $handler:expr ),* $(,)?
             }
         ),* $(,)?
     ) => {
         #[non_exhaustive]
-        #[derive(Debug, Clone)]
+        #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
         pub enum CGame<'node> {
             $($variant($variant<'node>),)*
+            /// A class only known to the node's [`ChunkRegistry`]
+            Extension(ExtensionNode),
+        }
+
+        /// A node read through a node reference, shared by every reference to it
+        #[derive(Debug, Clone)]
+        pub(crate) enum SharedNode<'node> {
+            $($variant(Arc<$variant<'node>>),)*
+            Extension(Arc<ExtensionNode>),
+            /// A node stored in another file
+            External(ExternalRef),
+        }
+
+        impl<'node> From<CGame<'node>> for SharedNode<'node> {
+            fn from(node: CGame<'node>) -> Self {
+                match node {
+                    $(CGame::$variant(variant) => SharedNode::$variant(Arc::new(variant)),)*
+                    CGame::Extension(node) => SharedNode::Extension(Arc::new(node)),
+                }
+            }
+        }
+
+        impl<'node> SharedNode<'node> {
+            /// 0 for external nodes, as the reference table doesn't say
+            pub fn class_id(&self) -> u32 {
+                match self {
+                    $(SharedNode::$variant(variant) => variant.class_id(),)*
+                    SharedNode::Extension(node) => node.class_id,
+                    SharedNode::External(_) => 0,
+                }
+            }
+
+            /// Takes the node out of its `Arc`, copying it if another reference still shares it.
+            /// `None` for external nodes.
+            pub fn into_node(self) -> Option<CGame<'node>> {
+                match self {
+                    $(SharedNode::$variant(variant) => Some(CGame::$variant(Arc::unwrap_or_clone(variant))),)*
+                    SharedNode::Extension(node) => Some(CGame::Extension(Arc::unwrap_or_clone(node))),
+                    SharedNode::External(_) => None,
+                }
+            }
         }
 
         impl<'node> CGame<'node> {
@@ -30,13 +79,45 @@ macro_rules! parser {
             pub(crate) fn parse(cursor: &mut BodyCursor<'node>, class_id: u32) -> Result<CGame<'node>, GbxError> {
                 match class_id {
                     $($class_id => Ok(CGame::$variant($variant::parse_full(cursor)?)),)*
+                    _ if cursor.registry().is_some_and(|registry| registry.has_class(class_id)) => {
+                        Ok(CGame::Extension(ExtensionNode::parse(cursor, class_id)?))
+                    }
+                    _ => Err(GbxErrorInner::InvalidClass(class_id).into())
+                }
+            }
+
+            /// Whether nodes of the class can be parsed, by a built-in class or `registry`
+            pub(crate) fn knows_class(class_id: u32, registry: Option<&ChunkRegistry>) -> bool {
+                [$($class_id,)*].contains(&class_id)
+                    || registry.is_some_and(|registry| registry.has_class(class_id))
+            }
+
+            /// A node of the given class with nothing parsed yet
+            pub(crate) fn empty(class_id: u32, registry: Option<&ChunkRegistry>) -> Result<CGame<'node>, GbxError> {
+                match class_id {
+                    $($class_id => Ok(CGame::$variant($variant::default())),)*
+                    _ if registry.is_some_and(|registry| registry.has_class(class_id)) => {
+                        Ok(CGame::Extension(ExtensionNode { class_id, ..Default::default() }))
+                    }
                     _ => Err(GbxErrorInner::InvalidClass(class_id).into())
                 }
             }
 
+            pub(crate) fn parse_recorded(
+                cursor: &mut BodyCursor<'node>,
+                class_id: u32,
+                chunks: &mut Vec<RawChunk<'node>>,
+            ) -> Result<CGame<'node>, GbxError> {
+                match class_id {
+                    $($class_id => Ok(CGame::$variant($variant::parse_recorded(cursor, Some(chunks))?)),)*
+                    _ => CGame::parse(cursor, class_id),
+                }
+            }
+
             pub(crate) fn parse_one(&mut self, cursor: &mut BodyCursor<'node>, full_chunk_id: u32) -> Result<(), GbxError> {
                 match self {
                     $(CGame::$variant(inner) => $variant::parse_one(cursor, inner, full_chunk_id)?,)*
+                    CGame::Extension(node) => node.parse_one(cursor, full_chunk_id)?,
                 };
                 Ok(())
             }
@@ -44,25 +125,70 @@ macro_rules! parser {
             pub fn class_id(&self) -> u32 {
                 match self {
                     $(CGame::$variant(variant) => variant.class_id(),)*
+                    CGame::Extension(node) => node.class_id,
+                }
+            }
+
+            /// Names of the `Option` fields that are `None`
+            pub(crate) fn missing_fields(&self) -> Vec<&'static str> {
+                match self {
+                    $(CGame::$variant(variant) => variant.missing_fields(),)*
+                    CGame::Extension(_) => Vec::new(),
+                }
+            }
+
+            /// Copies everything borrowed from the file, so the result can outlive it
+            pub fn into_owned(self) -> CGame<'static> {
+                match self {
+                    $(CGame::$variant(variant) => CGame::$variant(variant.into_owned()),)*
+                    CGame::Extension(node) => CGame::Extension(node),
                 }
             }
         }
 
+        impl IntoOwned for CGame<'_> {
+            type Owned = CGame<'static>;
+
+            fn into_owned(self) -> Self::Owned {
+                CGame::into_owned(self)
+            }
+        }
+
         $(
-            #[derive(Default, derivative::Derivative, Clone)]
-            #[derivative(Debug)]
+            #[derive(Default, derivative::Derivative, Clone, serde::Serialize, serde::Deserialize)]
+            #[derivative(Debug, PartialEq)]
             #[non_exhaustive]
             pub struct $variant<'node> {
-                phantom: std::marker::PhantomData<&'node ()>,
+                #[serde(skip)]
+                pub(crate) phantom: std::marker::PhantomData<&'node ()>,
                 $(
-                    $(#[ $ignore ])?
+                    $(#[ $attr ])*
                     pub $prop : $ty ,
                 )*
+                /// Results of the node's [`ChunkRegistry`] handlers
+                #[serde(skip)]
+                pub extensions: Extensions,
+            }
+
+            impl IntoOwned for $variant<'_> {
+                type Owned = $variant<'static>;
+
+                fn into_owned(self) -> Self::Owned {
+                    $variant {
+                        phantom: std::marker::PhantomData,
+                        $($prop: IntoOwned::into_owned(self.$prop),)*
+                        extensions: self.extensions,
+                    }
+                }
             }
 
             impl<'node> Parsable<'node> for $variant<'node> {
                 const CLASS_ID: u32 = $class_id;
 
+                fn extensions_mut(&mut self) -> &mut Extensions {
+                    &mut self.extensions
+                }
+
                 #[allow(unused_variables)]
                 fn handle_chunk(
                     &mut self,
@@ -95,18 +221,71 @@ macro_rules! parser {
                         .into())
                     }
                 }
+
+                fn coerce_shared(node: SharedNode<'node>) -> Result<Arc<Self>, GbxError> {
+                    match node {
+                        SharedNode::$variant(variant) => Ok(variant),
+                        _ => Err(GbxErrorInner::IncorrectType {
+                            wanted: Self::CLASS_ID,
+                            had: node.class_id(),
+                        }
+                        .into())
+                    }
+                }
             }
 
             impl<'node> $variant<'node> {
                 pub fn supports(chunk_id: u32) -> bool {
                     [$($chunk_id,)*].contains(&chunk_id)
                 }
+
+                /// Names of the `Option` fields that are `None`
+                pub(crate) fn missing_fields(&self) -> Vec<&'static str> {
+                    let mut missing = Vec::new();
+                    $(
+                        if (&FieldValue(&self.$prop)).is_missing() {
+                            missing.push(stringify!($prop));
+                        }
+                    )*
+                    missing
+                }
+
+                /// Copies everything borrowed from the file, so the result can outlive it
+                pub fn into_owned(self) -> $variant<'static> {
+                    IntoOwned::into_owned(self)
+                }
             }
         )*
     };
 }
 
-const LAST_CHUNK_ID: u32 = 0xfacade01;
+pub(crate) const LAST_CHUNK_ID: u32 = 0xfacade01;
+
+/// CGameBlockItem, the entity model of items made from blocks
+const BLOCK_ITEM_CLASS_ID: u32 = 0x2e025000;
+
+/// Lets `missing_fields` tell `Option` fields apart from others without knowing their types:
+/// method lookup tries `OptionField` on `&FieldValue<Option<T>>` before falling back to
+/// `OtherField` on `&&FieldValue<T>`.
+struct FieldValue<'a, T>(&'a T);
+
+trait OptionField {
+    fn is_missing(&self) -> bool;
+}
+
+impl<T> OptionField for FieldValue<'_, Option<T>> {
+    fn is_missing(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+trait OtherField {
+    fn is_missing(&self) -> bool {
+        false
+    }
+}
+
+impl<T> OtherField for &FieldValue<'_, T> {}
 
 pub(crate) trait Parsable<'node>: Sized + Default {
     const CLASS_ID: u32;
@@ -117,6 +296,10 @@ pub(crate) trait Parsable<'node>: Sized + Default {
 
     fn coerce(node: CGame<'node>) -> Result<Self, GbxError>;
 
+    fn coerce_shared(node: SharedNode<'node>) -> Result<Arc<Self>, GbxError>;
+
+    fn extensions_mut(&mut self) -> &mut Extensions;
+
     fn handle_chunk(
         &mut self,
         cursor: &mut BodyCursor<'node>,
@@ -127,9 +310,19 @@ pub(crate) trait Parsable<'node>: Sized + Default {
         cursor: &mut BodyCursor<'node>,
         this: &mut Self,
         full_chunk_id: u32,
+    ) -> Result<(), GbxError> {
+        cursor.in_chunk(full_chunk_id, |cursor| {
+            Self::parse_chunk(cursor, this, full_chunk_id)
+        })
+    }
+
+    fn parse_chunk(
+        cursor: &mut BodyCursor<'node>,
+        this: &mut Self,
+        full_chunk_id: u32,
     ) -> Result<(), GbxError> {
         let class_id = class_wrap(full_chunk_id & 0xffff_f000);
-        let wrapped_chunk_id = class_id + (full_chunk_id & 0xfff);
+        let wrapped_chunk_id = wrap_chunk_id(full_chunk_id);
 
         tracing::trace!(
             "full chunk ID: {:08x}; wrapped chunk ID {:08x}",
@@ -142,56 +335,141 @@ pub(crate) trait Parsable<'node>: Sized + Default {
             Self::CLASS_ID
         );
 
-        if cursor
-            .peek_u32_le()
+        let registry = cursor.registry();
+        let handler = registry
+            .as_deref()
+            .and_then(|registry| registry.handler(wrapped_chunk_id));
+
+        let mut skippable_end = None;
+        if let Some(chunk_data_size) = cursor
+            .read_skippable()
             .context("Peeking for skippable chunk")?
-            == 0x53_4b_49_50
         {
             tracing::trace!("skippable");
-            let _skip = cursor.read_u32::<LE>().context("Reading SKIP bytes")?;
-            let chunk_data_size = cursor
-                .read_u32::<LE>()
-                .context("Reading skippable data size")?;
 
-            if !CGame::supports(full_chunk_id) {
+            if handler.is_none() && !CGame::supports(full_chunk_id) {
                 tracing::warn!(
                     "Unsupported: class {:08x} chunk id {:08x}",
                     class_id,
                     wrapped_chunk_id
                 );
+                let offset = cursor.absolute_position();
+                cursor.report().skipped_chunks.push(SkippedChunk {
+                    chunk_id: full_chunk_id,
+                    class_id: Self::CLASS_ID,
+                    size: chunk_data_size,
+                    offset,
+                    header: cursor.is_header(),
+                });
                 cursor
                     .seek_relative(chunk_data_size as i64)
                     .context("Skipping skippable chunk")?;
                 return Ok(());
             }
+
+            // handlers for skippable chunks may only read the fields they care about
+            skippable_end = Some(cursor.position() + chunk_data_size as u64);
+        }
+
+        // header chunks have their size in the header, so the caller already set their end
+        if skippable_end.is_some() || !cursor.is_header() {
+            cursor.set_chunk_end(skippable_end);
+        }
+        let context = || {
+            format!(
+                "Handling chunk {:08x} for class {:08x}",
+                wrapped_chunk_id,
+                Self::CLASS_ID
+            )
+        };
+        match handler {
+            Some(handler) => {
+                let value = handler(&mut ChunkReader::new(cursor)).with_context(context)?;
+                this.extensions_mut().insert(wrapped_chunk_id, value);
+            }
+            None => this
+                .handle_chunk(cursor, wrapped_chunk_id)
+                .with_context(context)?,
         }
 
-        this.handle_chunk(cursor, wrapped_chunk_id)
-            .with_context(|| {
-                format!(
-                    "Handling chunk {:08x} for class {:08x}",
-                    wrapped_chunk_id,
-                    Self::CLASS_ID
-                )
-            })?;
+        if let Some(skippable_end) = skippable_end {
+            cursor.set_position(skippable_end);
+        }
 
         Ok(())
     }
 
     fn parse_full(cursor: &mut BodyCursor<'node>) -> Result<Self, GbxError> {
+        Self::parse_recorded(cursor, None)
+    }
+
+    fn parse_recorded(
+        cursor: &mut BodyCursor<'node>,
+        mut chunks: Option<&mut Vec<RawChunk<'node>>>,
+    ) -> Result<Self, GbxError> {
         let mut this = Self::default();
         loop {
-            let full_chunk_id = cursor.read_u32::<LE>().context("Reading full chunk ID")?;
+            let full_chunk_id = cursor.read_u32().context("Reading full chunk ID")?;
             if full_chunk_id == LAST_CHUNK_ID {
                 break;
             }
+
+            let start = cursor.position() as usize;
+            let lookback_before = chunks.is_some().then(|| cursor.lookback().clone());
             Self::parse_one(cursor, &mut this, full_chunk_id)?;
+
+            if let (Some(chunks), Some(lookback_before)) = (chunks.as_deref_mut(), lookback_before)
+            {
+                let data: &'node [u8] = cursor.get_ref();
+                let end = cursor.position() as usize;
+                chunks.push(RawChunk {
+                    id: full_chunk_id,
+                    heavy: false,
+                    data: data
+                        .get(start..end)
+                        .ok_or(GbxErrorInner::OutOfBounds { start, end })?,
+                    lookback_before,
+                    lookback_after: cursor.lookback().clone(),
+                });
+            }
         }
         Ok(this)
     }
 }
 
-fn class_wrap(class_id: u32) -> u32 {
+/// A chunk as it was stored in the file, kept so it can be written back unchanged
+#[derive(Debug, Clone)]
+pub(crate) struct RawChunk<'node> {
+    pub id: u32,
+    pub heavy: bool,
+    /// Everything after the chunk ID, including the skippable marker and size
+    pub data: &'node [u8],
+    pub lookback_before: Lookback<'node>,
+    pub lookback_after: Lookback<'node>,
+}
+
+impl<'node> RawChunk<'node> {
+    pub fn is_skippable(&self) -> bool {
+        self.data.starts_with(&SKIP.to_le_bytes())
+    }
+
+    /// The chunk data without the skippable marker and size
+    pub fn payload(&self) -> &'node [u8] {
+        if self.is_skippable() {
+            &self.data[8..]
+        } else {
+            self.data
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct RecordedChunks<'node> {
+    pub header: Vec<RawChunk<'node>>,
+    pub body: Vec<RawChunk<'node>>,
+}
+
+pub(crate) fn class_wrap(class_id: u32) -> u32 {
     match class_id {
         0x021080000 => 0x03043000, // CGameCtnChallenge (VSkipper)
         0x02108d000 => 0x03093000, // CGameCtnReplayRecord (VSkipper)
@@ -209,6 +487,241 @@ fn class_wrap(class_id: u32) -> u32 {
     }
 }
 
+/// `full_chunk_id` with its class ID wrapped by [`class_wrap`], which is the ID chunk handlers are
+/// registered and looked up by
+pub(crate) fn wrap_chunk_id(full_chunk_id: u32) -> u32 {
+    class_wrap(full_chunk_id & 0xffff_f000) + (full_chunk_id & 0xfff)
+}
+
+/// Reads MediaTracker keyframes that each hold a time and `num_values` other floats, returning
+/// just the times
+fn read_key_times(cursor: &mut BodyCursor<'_>, num_values: usize) -> Result<Vec<f32>, GbxError> {
+    let num_keys = cursor.read_u32().context("Reading number of keys")?;
+    let mut times = Vec::new();
+    for i in 0..num_keys {
+        times.push(
+            cursor
+                .read_f32()
+                .with_context(|| format!("Reading key {i} time"))?,
+        );
+        for _ in 0..num_values {
+            let _value = cursor
+                .read_f32()
+                .with_context(|| format!("Reading key {i} value"))?;
+        }
+    }
+    Ok(times)
+}
+
+/// Skips the rest of chunk `chunk_id`, for data that isn't understood. Only skippable chunks have
+/// a size to skip by, so this fails for others.
+fn skip_rest_of_chunk(cursor: &mut BodyCursor<'_>, chunk_id: u32) -> Result<(), GbxError> {
+    let Some(chunk_end) = cursor.chunk_end() else {
+        return Err(GbxErrorInner::UnknownChunkData(chunk_id).into());
+    };
+    cursor.set_position(chunk_end);
+    Ok(())
+}
+
+/// The common part of header chunk 0x2e001003, shared by everything that appears in the editor's
+/// catalog
+struct CollectorInfo<'node> {
+    info: Meta<'node>,
+    page_name: &'node str,
+    catalog_position: Option<u16>,
+    name: Option<&'node str>,
+}
+
+fn read_collector_info<'node>(
+    cursor: &mut BodyCursor<'node>,
+) -> Result<CollectorInfo<'node>, GbxError> {
+    let info = cursor.read_meta().context("Reading collector info")?;
+    let version = cursor.read_u32().context("Reading collector version")?;
+    let page_name = cursor.read_string().context("Reading page name")?;
+    if version == 5 {
+        let _unknown = cursor
+            .read_lookback_string()
+            .context("Reading collector unknown")?;
+    }
+    if version >= 4 {
+        let _parent_collector = cursor
+            .read_lookback_string()
+            .context("Reading parent collector")?;
+    }
+    let mut catalog_position = None;
+    if version >= 3 {
+        let _flags = cursor.read_u32().context("Reading collector flags")?;
+        catalog_position = Some(cursor.read_u16().context("Reading catalog position")?);
+    }
+    let mut name = None;
+    if version >= 7 {
+        name = Some(cursor.read_string().context("Reading collector name")?);
+    }
+    if version >= 8 {
+        let _prod_state = cursor.read_u8().context("Reading prod state")?;
+    }
+    Ok(CollectorInfo {
+        info,
+        page_name,
+        catalog_position,
+        name,
+    })
+}
+
+/// Header chunk 0x2e001004
+fn read_collector_icon<'node>(cursor: &mut BodyCursor<'node>) -> Result<ItemIcon<'node>, GbxError> {
+    let width = cursor.read_u16().context("Reading icon width")?;
+    let height = cursor.read_u16().context("Reading icon height")?;
+    let webp = (width & 0x8000) != 0 && (height & 0x8000) != 0;
+    let (width, height) = (width & 0x7fff, height & 0x7fff);
+    let data = if webp {
+        let _version = cursor.read_u16().context("Reading icon version")?;
+        let size = cursor.read_u32().context("Reading icon size")?;
+        cursor
+            .read_bytes(size as usize)
+            .context("Reading icon data")?
+    } else {
+        cursor
+            .read_bytes(width as usize * height as usize * 4)
+            .context("Reading icon data")?
+    };
+    Ok(ItemIcon {
+        width,
+        height,
+        webp,
+        data: data.into(),
+    })
+}
+
+/// Body chunk 0x2e001009
+fn skip_collector_page(cursor: &mut BodyCursor<'_>) -> Result<(), GbxError> {
+    let _page_name = cursor.read_string().context("Reading page name")?;
+    if cursor.read_bool().context("Reading has icon file")? {
+        let _icon = cursor.read_node_ref().context("Reading icon file")?;
+    }
+    let _parent_collector = cursor
+        .read_lookback_string()
+        .context("Reading parent collector")?;
+    Ok(())
+}
+
+/// Body chunk 0x2e001010
+fn skip_collector_skin(cursor: &mut BodyCursor<'_>) -> Result<(), GbxError> {
+    let version = cursor.read_u32().context("Reading skin version")?;
+    let _skin = cursor.read_node_ref().context("Reading skin")?;
+    let skin_directory = cursor.read_string().context("Reading skin directory")?;
+    if version >= 2 && skin_directory.is_empty() {
+        let _unknown = cursor.read_node_ref().context("Reading skin unknown")?;
+    }
+    Ok(())
+}
+
+/// Body chunk 0x2e001011, returning the catalog position
+fn read_collector_catalog(cursor: &mut BodyCursor<'_>) -> Result<u16, GbxError> {
+    let version = cursor.read_u32().context("Reading catalog version")?;
+    let _is_internal = cursor.read_bool().context("Reading is internal")?;
+    let _is_advanced = cursor.read_bool().context("Reading is advanced")?;
+    let catalog_position = cursor.read_u32().context("Reading catalog position")? as u16;
+    if version >= 1 {
+        let _prod_state = cursor.read_u8().context("Reading prod state")?;
+    }
+    Ok(catalog_position)
+}
+
+/// Reads the author and skin, or waypoint, that a block's flags say follow it
+fn read_block_extras<'node>(
+    cursor: &mut BodyCursor<'node>,
+    block: &mut Block<'node>,
+    i: usize,
+) -> Result<(), GbxError> {
+    if (block.flags & 0x8000) != 0 {
+        block.author = Some(
+            cursor
+                .read_lookback_string()
+                .with_context(|| format!("Reading block {i} author"))?
+                .into(),
+        );
+        block.skin = cursor
+            .expect_node_ref::<CtnBlockSkin>()
+            .with_context(|| format!("Reading block {i} skin"))?;
+    }
+
+    if (block.flags & 0x0010_0000) != 0 {
+        block.waypoint_special_property = cursor
+            .expect_node_ref::<WaypointSpecialProperty>()
+            .with_context(|| format!("Reading block {i} waypoint property"))?;
+    }
+    Ok(())
+}
+
+/// Parses a whole map file stored inside another file, within the same limits and with the same
+/// extra chunk handlers
+fn read_embedded_map(
+    cursor: &BodyCursor<'_>,
+    data: &[u8],
+) -> Result<CtnChallenge<'static>, GbxError> {
+    let mut node = Node::read_header(&data)?.with_limits(cursor.limits());
+    if let Some(registry) = cursor.registry() {
+        node = node.with_registry(registry);
+    }
+    match node.parse()? {
+        CGame::CtnChallenge(map) => Ok(map.into_owned()),
+        other => Err(GbxErrorInner::IncorrectType {
+            wanted: CtnChallenge::CLASS_ID,
+            had: other.class_id(),
+        }
+        .into()),
+    }
+}
+
+/// Chunk 0x03101002, which macroblocks also use for their items
+fn read_anchored_object<'node>(
+    cursor: &mut BodyCursor<'node>,
+    this: &mut CtnAnchoredObject<'node>,
+) -> Result<(), GbxError> {
+    let version = cursor.read_u32().context("Reading item version")?;
+    this.item_model = Some(cursor.read_meta().context("Reading item model")?);
+    this.yaw_pitch_roll = Some(cursor.read_vec3().context("Reading item rotation")?);
+    this.block_unit_coord = Some(
+        cursor
+            .read_byte3()
+            .context("Reading item block unit coord")?,
+    );
+    this.anchor_tree_id = Some(
+        cursor
+            .read_lookback_string()
+            .context("Reading item anchor tree ID")?
+            .into(),
+    );
+    this.position = Some(cursor.read_vec3().context("Reading item position")?);
+    this.waypoint_special_property = cursor
+        .expect_node::<WaypointSpecialProperty>()
+        .context("Reading item waypoint property")?;
+
+    if version >= 4 {
+        this.flags = Some(cursor.read_u16().context("Reading item flags")?);
+    }
+
+    if version >= 5 {
+        this.pivot_position = Some(cursor.read_vec3().context("Reading item pivot position")?);
+    }
+
+    if version >= 6 {
+        this.scale = Some(cursor.read_f32().context("Reading item scale")?);
+    }
+
+    if version >= 7 && this.flags.is_some_and(|flags| (flags & 0x4) != 0) {
+        this.skin = Some(cursor.read_file_ref().context("Reading item skin")?);
+    }
+
+    if version >= 8 {
+        let _unknown = cursor.read_vec3().context("Reading item unknown 1")?;
+        let _unknown = cursor.read_vec3().context("Reading item unknown 2")?;
+    }
+
+    Ok(())
+}
+
 fn negative_none(num: i32) -> Option<i32> {
     if num < 0 {
         None
@@ -219,44 +732,77 @@ fn negative_none(num: i32) -> Option<i32> {
 
 parser!(
     0x03043000 CtnChallenge {
-        map_name: Option<&'node str>,
+        map_name: Option<Cow<'node, str>>,
         vehicle_model: Option<Meta<'node>>,
-        block_stock: Option<CtnCollectorList<'node>>,
-        challenge_parameters: Option<CtnChallengeParameters<'node>>,
+        block_stock: Option<NodeRef<CtnCollectorList<'node>>>,
+        challenge_parameters: Option<NodeRef<CtnChallengeParameters<'node>>>,
         map_kind: Option<MapKind>,
         header_map_kind: Option<MapKind>,
         map_info: Option<Meta<'node>>,
         decoration: Option<Meta<'node>>,
         size: Option<[u32; 3]>,
+        #[derivative(Debug = "ignore")]
+        blocks: Vec<Block<'node>>,
+        #[derivative(Debug = "ignore")]
+        items: Vec<CtnAnchoredObject<'node>>,
+        embedded_item_models: Vec<Meta<'node>>,
+        #[derivative(Debug = "ignore")]
+        #[serde(with = "crate::base64_bytes::option")]
+        embedded_zip: Option<Cow<'node, [u8]>>,
+        embedded_textures: Vec<Cow<'node, str>>,
         bronze_time: Option<i32>,
         silver_time: Option<i32>,
         gold_time: Option<i32>,
         author_time: Option<i32>,
         cost: Option<u32>,
+        is_lap_race: Option<bool>,
+        num_laps: Option<u32>,
+        num_checkpoints: Option<u32>,
+        play_mode: Option<PlayMode>,
+        editor_mode: Option<EditorMode>,
+        has_ghost_blocks: Option<bool>,
+        gamepad_editor: Option<bool>,
+        password: Option<Cow<'node, str>>,
+        map_coord_origin: Option<[f32; 2]>,
+        map_coord_target: Option<[f32; 2]>,
+        pack_mask: Option<u128>,
+        map_type: Option<Cow<'node, str>>,
+        map_style: Option<Cow<'node, str>>,
+        lightmap_version: Option<u8>,
+        title_id: Option<Cow<'node, str>>,
         header_version: Option<u32>,
-        xml_data: Option<&'node str>,
+        xml_data: Option<Cow<'node, str>>,
+        mod_pack: Option<FileRef<'node>>,
+        custom_music: Option<FileRef<'node>>,
+        clip_intro: Option<NodeRef<CtnMediaClip<'node>>>,
+        clip_podium: Option<NodeRef<CtnMediaClip<'node>>>,
+        clip_group_in_game: Option<NodeRef<CtnMediaClipGroup<'node>>>,
+        clip_group_end_race: Option<NodeRef<CtnMediaClipGroup<'node>>>,
+        clip_ambiance: Option<NodeRef<CtnMediaClip<'node>>>,
+        clip_trigger_size: Option<[u32; 3]>,
         #[derivative(Debug = "ignore")]
-        thumbnail_data: Option<&'node [u8]>,
-        author_login: Option<&'node str>,
-        author_nickname: Option<&'node str>,
-        author_zone: Option<&'node str>,
-        author_extra_info: Option<&'node str>,
+        #[serde(with = "crate::base64_bytes::option")]
+        thumbnail_data: Option<Cow<'node, [u8]>>,
+        author_login: Option<Cow<'node, str>>,
+        author_nickname: Option<Cow<'node, str>>,
+        author_zone: Option<Cow<'node, str>>,
+        author_extra_info: Option<Cow<'node, str>>,
     } {
         0x03043002 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
             let version = cursor.read_u8().context("Reading map info 1 version")?;
 
             if version <= 2 {
                 this.map_info = Some(cursor.read_meta().context("Reading map info 1 map info")?);
-                this.map_name = Some(cursor.read_string().context("Reading map info 1 map name")?);
+                this.map_name = Some(cursor.read_string().context("Reading map info 1 map name")?.into());
             }
 
-            let _unknown = cursor.read_u32::<LE>().context("Reading map info 1 unknown 1");
+            let _unknown = cursor.read_u32().context("Reading map info 1 unknown 1");
 
             if version >= 1 {
-                this.bronze_time = negative_none(cursor.read_i32::<LE>().context("Reading map info 1 bronze time")?);
-                this.silver_time = negative_none(cursor.read_i32::<LE>().context("Reading map info 1 silver time")?);
-                this.gold_time = negative_none(cursor.read_i32::<LE>().context("Reading map info 1 gold time")?);
-                this.author_time = negative_none(cursor.read_i32::<LE>().context("Reading map info 1 author time")?);
+                this.bronze_time = negative_none(cursor.read_i32().context("Reading map info 1 bronze time")?);
+                this.silver_time = negative_none(cursor.read_i32().context("Reading map info 1 silver time")?);
+                this.gold_time = negative_none(cursor.read_i32().context("Reading map info 1 gold time")?);
+                this.author_time = negative_none(cursor.read_i32().context("Reading map info 1 author time")?);
             }
 
             if version == 2 {
@@ -264,40 +810,48 @@ parser!(
             }
 
             if version >= 4 {
-                this.cost = Some(cursor.read_u32::<LE>().context("Reading map info 1 cost (coppers (riolu LOL XD XD XD XD)")?);
+                this.cost = Some(cursor.read_u32().context("Reading map info 1 cost (coppers (riolu LOL XD XD XD XD)")?);
             }
 
             if version >= 5 {
-                let _is_lap_race = cursor.read_u32::<LE>().context("Reading lap race")?;
+                this.is_lap_race = Some(cursor.read_u32().context("Reading lap race")? != 0);
             }
 
             if version == 6 {
-                let _is_multilap = cursor.read_u32::<LE>().context("Reading is multilap")?;
+                let _is_multilap = cursor.read_u32().context("Reading is multilap")?;
             }
 
             if version >= 7 {
-                let _play_mode = cursor.read_u32::<LE>().context("Reading play mode")?;
+                let play_mode = cursor.read_u32().context("Reading play mode")?;
+                this.play_mode = u8::try_from(play_mode).ok().and_then(PlayMode::from_repr);
             }
 
             if version >= 9 {
-                let _unknown = cursor.read_u32::<LE>().context("Reading map info 1 unknown 3")?;
+                let _unknown = cursor.read_u32().context("Reading map info 1 unknown 3")?;
             }
 
             if version >= 10 {
-                let _author_score = cursor.read_u32::<LE>().context("Reading map info 1 author score")?;
+                let _author_score = cursor.read_u32().context("Reading map info 1 author score")?;
             }
 
             if version >= 11 {
-                let _editor_mode = cursor.read_u32::<LE>().context("Reading editor mode")?;
+                let editor_mode = cursor.read_u32().context("Reading editor mode")?;
+                this.editor_mode = Some(if editor_mode & EDITOR_MODE_SIMPLE != 0 {
+                    EditorMode::Simple
+                } else {
+                    EditorMode::Advanced
+                });
+                this.has_ghost_blocks = Some(editor_mode & EDITOR_MODE_GHOST_BLOCKS != 0);
+                this.gamepad_editor = Some(editor_mode & EDITOR_MODE_GAMEPAD != 0);
             }
 
             if version >= 12 {
-                let _unknown = cursor.read_u32::<LE>().context("Reading map info 1 unknown 4")?;
+                let _unknown = cursor.read_u32().context("Reading map info 1 unknown 4")?;
             }
 
             if version >= 13 {
-                let _num_checkpoints = cursor.read_u32::<LE>().context("Reading num checkpoints")?;
-                let _num_laps = cursor.read_u32::<LE>().context("Reading num laps")?;
+                this.num_checkpoints = Some(cursor.read_u32().context("Reading num checkpoints")?);
+                this.num_laps = Some(cursor.read_u32().context("Reading num laps")?);
             }
 
             Ok(())
@@ -307,12 +861,12 @@ parser!(
             let version = cursor.read_u8().context("Reading map info 2 version")?;
 
             this.map_info = Some(cursor.read_meta().context("Reading map info 2 map info")?);
-            this.map_name = Some(cursor.read_string().context("Reading map info 2 map name")?);
+            this.map_name = Some(cursor.read_string().context("Reading map info 2 map name")?.into());
             this.header_map_kind = MapKind::from_repr(cursor.read_u8().context("Reading map info 2 map kind")?);
 
             if version >= 1 {
-                let _unknown = cursor.read_u32::<LE>().context("Reading map info 2 unknown 1")?;
-                let _password = cursor.read_string().context("Reading map info 2 password")?;
+                let _unknown = cursor.read_u32().context("Reading map info 2 unknown 1")?;
+                this.password = Some(cursor.read_string().context("Reading map info 2 password")?.into());
             }
 
             if version >= 2 {
@@ -320,54 +874,52 @@ parser!(
             }
 
             if version >= 3 {
-                let _map_coord_origin = cursor.read_vec2().context("Reading map info 2 map coord origin")?;
+                this.map_coord_origin = Some(cursor.read_vec2().context("Reading map info 2 map coord origin")?);
             }
 
             if version >= 4 {
-                let _map_coord_target = cursor.read_vec2().context("Reading map info 2 map coord target")?;
+                this.map_coord_target = Some(cursor.read_vec2().context("Reading map info 2 map coord target")?);
             }
 
             if version >= 5 {
-                let _pack_mask = cursor.read_u128::<LE>().context("Reading map info 2 pack mask")?;
+                this.pack_mask = Some(cursor.read_u128().context("Reading map info 2 pack mask")?);
             }
 
             if version >= 6 {
-                let _map_type = cursor.read_string().context("Reading map info 2 map type")?;
-                let _map_style = cursor.read_string().context("Reading map info 2 map style")?;
+                this.map_type = Some(cursor.read_string().context("Reading map info 2 map type")?.into());
+                this.map_style = Some(cursor.read_string().context("Reading map info 2 map style")?.into());
             }
 
             if version >= 8 {
-                let _lightmap_cache_uid = cursor.read_u64::<LE>().context("Reading map info 2 lightmap cache uid")?;
+                let _lightmap_cache_uid = cursor.read_u64().context("Reading map info 2 lightmap cache uid")?;
             }
 
             if version >= 9 {
-                let _lightmap_version = cursor.read_u8().context("Reading map info 2 lightmap version")?;
+                this.lightmap_version = Some(cursor.read_u8().context("Reading map info 2 lightmap version")?);
             }
 
             if version >= 11 {
-                let _title_id = cursor.read_lookback_string().context("Reading map info 2 title ID")?;
+                this.title_id = Some(cursor.read_lookback_string().context("Reading map info 2 title ID")?.into());
             }
 
             Ok(())
         },
 
         0x03043004 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            this.header_version = Some(cursor.read_u32::<LE>().context("Reading header version")?);
+            this.header_version = Some(cursor.read_u32().context("Reading header version")?);
             Ok(())
         },
 
         0x03043005 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            this.xml_data = Some(cursor.read_string().context("Reading XML data")?);
+            this.xml_data = Some(cursor.read_string().context("Reading XML data")?.into());
             Ok(())
         },
 
         0x03043007 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            let _version = cursor.read_u32::<LE>().context("Reading thumbnail version")?;
-            let thumbnail_size = cursor.read_u32::<LE>().context("Reading thumbnail size")? as usize;
+            let _version = cursor.read_u32().context("Reading thumbnail version")?;
+            let thumbnail_size = cursor.read_u32().context("Reading thumbnail size")? as usize;
             let _thumbnail_start_tag = cursor.read_string_exact("<Thumbnail.jpg>".len()).context("Reading thumbnail start tag")?;
-            let position = cursor.position() as usize;
-            this.thumbnail_data = Some(&cursor.get_ref()[position..=position + thumbnail_size]);
-            cursor.seek_relative(thumbnail_size as i64).context("Seeking after reading thumbnail data")?;
+            this.thumbnail_data = Some(cursor.read_bytes(thumbnail_size).context("Reading thumbnail data")?.into());
             let _thumbnail_end_tag = cursor.read_string_exact("</Thumbnail.jpg>".len()).context("Reading thumbnail end tag")?;
             let _comments_start_tag = cursor.read_string_exact("<Comments>".len()).context("Reading comments start tag")?;
             let _comments = cursor.read_string().context("Reading comments")?;
@@ -376,12 +928,12 @@ parser!(
         },
 
         0x03043008 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            let _version = cursor.read_u32::<LE>().context("Reading author information")?;
-            let _author_version = cursor.read_u32::<LE>().context("Reading author version")?;
-            this.author_login = Some(cursor.read_string().context("Reading author login")?);
-            this.author_nickname = Some(cursor.read_string().context("Reading author nickname")?);
-            this.author_zone = Some(cursor.read_string().context("Reading author zone")?);
-            this.author_extra_info = Some(cursor.read_string().context("Reading author extra info")?);
+            let _version = cursor.read_u32().context("Reading author information")?;
+            let _author_version = cursor.read_u32().context("Reading author version")?;
+            this.author_login = Some(cursor.read_string().context("Reading author login")?.into());
+            this.author_nickname = Some(cursor.read_string().context("Reading author nickname")?.into());
+            this.author_zone = Some(cursor.read_string().context("Reading author zone")?.into());
+            this.author_extra_info = Some(cursor.read_string().context("Reading author extra info")?.into());
             Ok(())
         },
 
@@ -397,202 +949,627 @@ parser!(
             this.challenge_parameters = cursor
                 .expect_node_ref::<CtnChallengeParameters>()
                 .context("Reading challenge parameters")?;
-            this.map_kind = MapKind::from_repr(cursor.read_u32::<LE> ().context("Reading map info 2 map kind")? as u8);
+            let map_kind = cursor.read_u32().context("Reading map info 2 map kind")?;
+            this.map_kind = u8::try_from(map_kind).ok().and_then(MapKind::from_repr);
             Ok(())
         },
 
         0x03043022 => |_this: &mut CtnChallenge, cursor: &mut BodyCursor| -> Result<(), GbxError> {
-            cursor.read_u32::<LE>().context("Reading unknown")?;
+            cursor.read_u32().context("Reading unknown")?;
+            Ok(())
+        },
+
+        0x03043019 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.mod_pack = Some(cursor.read_file_ref().context("Reading mod pack file reference")?);
             Ok(())
         },
 
-        0x03043024 => |_this: &mut CtnChallenge, cursor: &mut BodyCursor| -> Result<(), GbxError> {
-            cursor.read_file_ref().context("Reading music file reference")?;
+        0x03043024 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.custom_music = Some(cursor.read_file_ref().context("Reading music file reference")?);
             Ok(())
         },
 
         0x0304302a => |_this: &mut CtnChallenge, cursor: &mut BodyCursor| -> Result<(), GbxError> {
-            cursor.read_u32::<LE>().context("Reading simple editor")?;
+            cursor.read_u32().context("Reading simple editor")?;
             Ok(())
         },
 
         0x03043025 => |_this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            cursor.read_f32::<LE>().context("Reading origin X")?;
-            cursor.read_f32::<LE>().context("Reading origin Y")?;
-            cursor.read_f32::<LE>().context("Reading target X")?;
-            cursor.read_f32::<LE>().context("Reading target Y")?;
+            cursor.read_f32().context("Reading origin X")?;
+            cursor.read_f32().context("Reading origin Y")?;
+            cursor.read_f32().context("Reading target X")?;
+            cursor.read_f32().context("Reading target Y")?;
             Ok(())
         },
 
+        // MediaTracker clips
         0x03043049 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            cursor.force_skip(0x03043049)?;
+            let start = cursor.position();
+            let lookback = cursor.lookback().clone();
+            let nodes = cursor.nodes();
+            let clips = (|| -> Result<(), GbxError> {
+                let version = cursor.read_u32().context("Reading clips version")?;
+                this.clip_intro = cursor.expect_node_ref::<CtnMediaClip>().context("Reading intro clip")?;
+                this.clip_podium = cursor.expect_node_ref::<CtnMediaClip>().context("Reading podium clip")?;
+                this.clip_group_in_game = cursor.expect_node_ref::<CtnMediaClipGroup>().context("Reading in-game clip group")?;
+                this.clip_group_end_race = cursor.expect_node_ref::<CtnMediaClipGroup>().context("Reading end race clip group")?;
+                if version >= 2 {
+                    this.clip_ambiance = cursor.expect_node_ref::<CtnMediaClip>().context("Reading ambiance clip")?;
+                }
+                if version >= 1 {
+                    this.clip_trigger_size = Some(cursor.read_int3().context("Reading clip trigger size")?);
+                }
+                Ok(())
+            })();
+
+            // not every media block is understood, but the rest of the map still is. Limits
+            // still apply, though.
+            if let Err(err) = clips {
+                if err.is_limit() {
+                    return Err(err);
+                }
+                tracing::warn!("Couldn't parse MediaTracker clips: {}", err);
+                this.clip_intro = None;
+                this.clip_podium = None;
+                this.clip_group_in_game = None;
+                this.clip_group_end_race = None;
+                this.clip_ambiance = None;
+                cursor.set_position(start);
+                cursor.set_lookback(lookback);
+                cursor.set_nodes(nodes);
+                cursor.force_skip(0x03043049)?;
+            }
             Ok(())
         },
 
         0x0304301f => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
             tracing::trace!("block data");
             this.map_info = Some(cursor.read_meta().context("Reading map info")?);
-            this.map_name = Some(cursor.read_string().context("Reading map name")?);
+            this.map_name = Some(cursor.read_string().context("Reading map name")?.into());
             this.decoration = Some(cursor.read_meta().context("Reading decoration")?);
             this.size = Some(cursor.read_int3().context("Reading map size")?);
 
-            let _need_unlock = cursor.read_u32::<LE>().context("Reading needs unlock")?;
-            let version = cursor.read_u32::<LE>().context("Reading block data version")?;
-            let num_blocks = cursor.read_u32::<LE>().context("Reading number of blocks")?;
+            let _need_unlock = cursor.read_u32().context("Reading needs unlock")?;
+            let version = cursor.read_u32().context("Reading block data version")?;
+            let num_blocks = cursor.read_u32().context("Reading number of blocks")?;
 
-            fn parse_block(cursor: &mut BodyCursor, version: u32, i: u32) -> Result<bool, GbxError> {
-                let block_name = cursor.read_lookback_string().with_context(|| format!("Reading block {i} name"))?;
+            fn parse_block<'node>(cursor: &mut BodyCursor<'node>, version: u32, i: usize) -> Result<Option<Block<'node>>, GbxError> {
+                let name = cursor.read_lookback_string().with_context(|| format!("Reading block {i} name"))?;
                 let direction = cursor.read_u8().with_context(|| format!("Reading block {i} direction"))?;
                 let position = cursor.read_byte3().with_context(|| format!("Reading block {i} position"))?;
 
                 let flags = if version == 0 {
-                    cursor.read_u16::<LE>().with_context(|| format!("Reading block {i} flags (u16)"))? as u32
+                    cursor.read_u16().with_context(|| format!("Reading block {i} flags (u16)"))? as u32
                 } else {
-                    cursor.read_u32::<LE>().with_context(|| format!("Reading block {i} flags (u16)"))?
+                    cursor.read_u32().with_context(|| format!("Reading block {i} flags (u16)"))?
                 };
 
                 if flags == 0xffff_ffff {
-                    Ok(false)
-                } else {
-                    if (flags & 0x8000) != 0 {
-                        let author = cursor.read_lookback_string().with_context(|| format!("Reading block {i} author"))?;
-                        let skin = cursor.expect_node_ref::<CtnBlockSkin>().with_context(|| format!("Reading block {i} skin"))?;
-                    }
+                    return Ok(None);
+                }
 
-                    if (flags & 0x0010_0000) != 0 {
-                        let waypoint_special_property = cursor
-                            .expect_node_ref::<WaypointSpecialProperty>()
-                            .with_context(|| format!("Reading block {i} waypoint property"))?;
-                    }
+                let mut block = Block::new(name.into(), Direction::from_repr(direction), position, flags);
+                read_block_extras(cursor, &mut block, i)?;
 
-                    Ok(true)
-                }
+                Ok(Some(block))
             }
 
             let mut i = 0;
             loop {
-                if parse_block(cursor, version, i)? {
+                if let Some(block) = parse_block(cursor, version, this.blocks.len())? {
+                    this.blocks.push(block);
                     i += 1;
                 }
                 if i >= num_blocks {
                     break;
                 }
             }
-            while (cursor.peek_u32_le().context("Peeking for more blocks")? & 0xc000_0000) > 0 {
-                parse_block(cursor, version, 222222)?;
+            while (cursor.peek_u32().context("Peeking for more blocks")? & 0xc000_0000) > 0 {
+                if let Some(block) = parse_block(cursor, version, this.blocks.len())? {
+                    this.blocks.push(block);
+                }
             }
 
             Ok(())
         },
-    },
 
-    0x0305b000 CtnChallengeParameters {
-        author_score: Option<i32>,
-        bronze_time: Option<i32>,
-        silver_time: Option<i32>,
-        gold_time: Option<i32>,
-        author_time: Option<i32>,
-        map_type: Option<&'node str>,
-        map_style: Option<&'node str>,
-        is_validated: Option<bool>,
-        validation_ghost: Option<CtnGhost<'node>>,
-        time_limit: Option<i32>,
-        tip: Option<&'node str>,
-        tip1: Option<&'node str>,
-        tip2: Option<&'node str>,
-        tip3: Option<&'node str>,
-        tip4: Option<&'node str>,
-    } {
-        0x0305b001 => |this: &mut CtnChallengeParameters<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            tracing::trace!("tips");
-            this.tip1 = Some(cursor.read_string().context("Reading tip 1")?);
-            this.tip2 = Some(cursor.read_string().context("Reading tip 2")?);
-            this.tip3 = Some(cursor.read_string().context("Reading tip 3")?);
-            this.tip4 = Some(cursor.read_string().context("Reading tip 4")?);
+        0x03043040 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let version = cursor.read_u32().context("Reading items version")?;
+            if version == 0 {
+                return Ok(());
+            }
+
+            let _unknown = cursor.read_u32().context("Reading items unknown")?;
+            let size = cursor.read_u32().context("Reading items data size")?;
+
+            // items have their own lookback strings and node references
+            let data = cursor.read_bytes(size as usize).context("Reading items data")?;
+            let mut cursor = cursor.encapsulated(data);
+
+            let _list_version = cursor.read_u32().context("Reading items list version")?;
+            let num_items = cursor.read_u32().context("Reading number of items")?;
+            for i in 0..num_items {
+                let class_id = cursor.read_u32().with_context(|| format!("Reading item {i} class ID"))?;
+                if class_id != CtnAnchoredObject::CLASS_ID {
+                    return Err(GbxErrorInner::IncorrectType {
+                        wanted: CtnAnchoredObject::CLASS_ID,
+                        had: class_id,
+                    })
+                    .with_context(|| format!("Reading item {i}"));
+                }
+                this.items.push(CtnAnchoredObject::parse_full(&mut cursor).with_context(|| format!("Reading item {i}"))?);
+            }
+
+            // the rest describes which blocks and items each item is snapped to
+
             Ok(())
         },
 
-        0x0305b004 => |this: &mut CtnChallengeParameters<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            tracing::trace!("medal times");
-            this.bronze_time = negative_none(cursor.read_i32::<LE>().context("Reading bronze time")?);
-            this.silver_time = negative_none(cursor.read_i32::<LE>().context("Reading silver time")?);
-            this.gold_time = negative_none(cursor.read_i32::<LE>().context("Reading gold time")?);
-            this.author_time = negative_none(cursor.read_i32::<LE>().context("Reading author time")?);
-            let _unknown = cursor.read_u32::<LE>().context("Reading unknown medal time parameter")?;
+        0x03043054 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading embedded items version")?;
+            let _unknown = cursor.read_u32().context("Reading embedded items unknown")?;
+            let size = cursor.read_u32().context("Reading embedded items data size")?;
+
+            // embedded items have their own lookback strings
+            let data = cursor.read_bytes(size as usize).context("Reading embedded items data")?;
+            let mut cursor = cursor.encapsulated(data);
+
+            let num_item_models = cursor.read_u32().context("Reading number of embedded item models")?;
+            for i in 0..num_item_models {
+                this.embedded_item_models.push(
+                    cursor
+                        .read_meta()
+                        .with_context(|| format!("Reading embedded item model {i}"))?,
+                );
+            }
+
+            let zip_size = cursor.read_u32().context("Reading embedded archive size")?;
+            this.embedded_zip = Some(cursor.read_bytes(zip_size as usize).context("Reading embedded archive")?.into());
+
+            let num_textures = cursor.read_u32().context("Reading number of embedded textures")?;
+            for i in 0..num_textures {
+                this.embedded_textures.push(
+                    cursor
+                        .read_string()
+                        .with_context(|| format!("Reading embedded texture {i}"))?
+                        .into(),
+                );
+            }
+
             Ok(())
         },
 
-        0x0305b008 => |this: &mut CtnChallengeParameters<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            tracing::trace!("stunt info");
-            this.time_limit = negative_none(cursor.read_i32::<LE>().context("Reading time limit")?);
-            this.author_score = negative_none(cursor.read_i32::<LE>().context("Reading author score")?);
+        // block, baked block, and item colors
+        0x03043062 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading colors version")?;
+            let Some(chunk_end) = cursor.chunk_end() else {
+                return Ok(());
+            };
+
+            let colors = cursor.read_bytes(chunk_end.saturating_sub(cursor.position()) as usize).context("Reading colors")?;
+            let Some(item_colors) = colors.get(colors.len().saturating_sub(this.items.len())..) else {
+                return Ok(());
+            };
+            for (item, color) in this.items.iter_mut().zip(item_colors) {
+                item.color = DifficultyColor::from_repr(*color);
+            }
+
             Ok(())
         },
 
-        0x0305b00a => |this: &mut CtnChallengeParameters<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            tracing::trace!("skippable medal times");
-            this.tip = Some(cursor.read_string().context("Reading skippable medal times tip")?);
-            this.bronze_time = negative_none(cursor.read_i32::<LE>().context("Reading skippable bronze time")?);
-            this.silver_time = negative_none(cursor.read_i32::<LE>().context("Reading skippable silver time")?);
-            this.gold_time = negative_none(cursor.read_i32::<LE>().context("Reading skippable gold time")?);
-            this.author_time = negative_none(cursor.read_i32::<LE>().context("Reading skippable author time")?);
-            this.time_limit = negative_none(cursor.read_i32::<LE>().context("Reading skippable time limit")?);
-            this.author_score = negative_none(cursor.read_i32::<LE>().context("Reading skippable author score")?);
+        0x03043063 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading animation phase offsets version")?;
+            for (i, item) in this.items.iter_mut().enumerate() {
+                item.animation_phase_offset = PhaseOffset::from_repr(
+                    cursor.read_u8().with_context(|| format!("Reading item {i} animation phase offset"))?,
+                );
+            }
             Ok(())
         },
 
-        0x0305b00d => |this: &mut CtnChallengeParameters<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            tracing::trace!("stunt info");
-            this.validation_ghost = cursor.expect_node_ref::<CtnGhost>().context("Reading validation ghost")?;
+        0x03043065 => |this: &mut CtnChallenge<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading item foreground skins version")?;
+            for (i, item) in this.items.iter_mut().enumerate() {
+                let has_skin = cursor.read_u8().with_context(|| format!("Reading item {i} has foreground skin"))?;
+                if has_skin != 0 {
+                    item.foreground_skin = Some(cursor.read_file_ref().with_context(|| format!("Reading item {i} foreground skin"))?);
+                }
+            }
             Ok(())
         },
     },
 
-    0x03092000 CtnGhost {} {},
-
-    0x2e009000 WaypointSpecialProperty {
-        order: Option<u32>,
-        spawn: Option<u32>,
-        tag: Option<&'node str>,
+    0x03093000 CtnReplayRecord {
+        header_version: Option<u32>,
+        map_info: Option<Meta<'node>>,
+        time: Option<i32>,
+        player_nickname: Option<Cow<'node, str>>,
+        player_login: Option<Cow<'node, str>>,
+        title_id: Option<Cow<'node, str>>,
+        xml_data: Option<Cow<'node, str>>,
+        game_version: Option<Cow<'node, str>>,
+        author_login: Option<Cow<'node, str>>,
+        author_nickname: Option<Cow<'node, str>>,
+        author_zone: Option<Cow<'node, str>>,
+        author_extra_info: Option<Cow<'node, str>>,
+        /// The map the replay was driven on, as a whole file, for saving it on its own
+        #[derivative(Debug = "ignore")]
+        #[serde(with = "crate::base64_bytes::option")]
+        map_data: Option<Cow<'node, [u8]>>,
+        #[derivative(Debug = "ignore")]
+        map: Option<Arc<CtnChallenge<'node>>>,
+        ghosts: Vec<NodeRef<CtnGhost<'node>>>,
+        extras: Vec<u64>,
+        clip: Option<NodeRef<CtnMediaClip<'node>>>,
     } {
-        0x2e009000 => |this: &mut WaypointSpecialProperty<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            let version = cursor.read_u32::<LE>().context("Reading waypoint data version")?;
+        0x03093000 => |this: &mut CtnReplayRecord<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let version = cursor.read_u32().context("Reading replay info version")?;
+            this.header_version = Some(version);
 
-            if version == 1 {
-                this.spawn = Some(cursor.read_u32::<LE>().context("Reading waypoint spawn")?);
-                this.order = Some(cursor.read_u32::<LE>().context("Reading waypoint order")?);
-            } else {
-                this.tag = Some(cursor.read_string().context("Reading waypoint tag")?);
-                this.order = Some(cursor.read_u32::<LE>().context("Reading waypoint order")?);
+            if version >= 2 {
+                this.map_info = Some(cursor.read_meta().context("Reading replay map info")?);
+                this.time = negative_none(cursor.read_i32().context("Reading replay time")?);
+                this.player_nickname = Some(cursor.read_string().context("Reading replay player nickname")?.into());
+            }
+
+            if version >= 6 {
+                this.player_login = Some(cursor.read_string().context("Reading replay player login")?.into());
+            }
+
+            if version >= 8 {
+                let _unknown = cursor.read_u8().context("Reading replay info unknown")?;
+                this.title_id = Some(cursor.read_lookback_string().context("Reading replay title ID")?.into());
             }
 
             Ok(())
         },
-    },
 
-    0x03059000 CtnBlockSkin {
-        foreground_pack_desc: Option<&'node str>,
-        pack_desc: Option<&'node str>,
-        parent_pack_desc: Option<&'node str>,
-        text: Option<&'node str>,
-    } {
-        // text
-        0x03059000 => |this: &mut CtnBlockSkin<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            this.text = Some(cursor.read_string().context("Reading block text")?);
-            let _unknown = cursor.read_string().context("Reading block text unknown")?;
+        0x03093001 => |this: &mut CtnReplayRecord<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let xml_data = cursor.read_string().context("Reading replay XML data")?;
+            this.xml_data = Some(xml_data.into());
+            this.game_version = xml::element(xml_data, "header").and_then(|header| xml::attribute(header, "exever"));
             Ok(())
         },
 
-        // skin
-        0x03059001 => |this: &mut CtnBlockSkin<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            this.text = Some(cursor.read_string().context("Reading block skin text")?);
+        // author information in the header, embedded map in the body
+        0x03093002 => |this: &mut CtnReplayRecord<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            if cursor.is_header() {
+                let _version = cursor.read_u32().context("Reading replay author information")?;
+                let _author_version = cursor.read_u32().context("Reading replay author version")?;
+                this.author_login = Some(cursor.read_string().context("Reading replay author login")?.into());
+                this.author_nickname = Some(cursor.read_string().context("Reading replay author nickname")?.into());
+                this.author_zone = Some(cursor.read_string().context("Reading replay author zone")?.into());
+                this.author_extra_info = Some(cursor.read_string().context("Reading replay author extra info")?.into());
+            } else {
+                let map_size = cursor.read_u32().context("Reading replay map size")?;
+                let map_data = cursor.read_bytes(map_size as usize).context("Reading replay map data")?;
+                if !map_data.is_empty() {
+                    this.map = Some(Arc::new(read_embedded_map(cursor, map_data).context("Parsing replay map")?));
+                }
+                this.map_data = Some(map_data.into());
+            }
+            Ok(())
+        },
+
+        0x03093014 => |this: &mut CtnReplayRecord<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading replay ghosts version")?;
+            let num_ghosts = cursor.read_u32().context("Reading replay number of ghosts")?;
+            for i in 0..num_ghosts {
+                if let Some(ghost) = cursor.expect_node_ref::<CtnGhost>().with_context(|| format!("Reading replay ghost {i}"))? {
+                    this.ghosts.push(ghost);
+                }
+            }
+
+            let _unknown = cursor.read_u32().context("Reading replay ghosts unknown")?;
+            let num_extras = cursor.read_u32().context("Reading replay number of extras")?;
+            for i in 0..num_extras {
+                this.extras.push(cursor.read_u64().with_context(|| format!("Reading replay extra {i}"))?);
+            }
+
+            Ok(())
+        },
+
+        0x03093015 => |this: &mut CtnReplayRecord<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.clip = cursor.expect_node_ref::<CtnMediaClip>().context("Reading replay clip")?;
+            Ok(())
+        },
+
+        0x03093018 => |this: &mut CtnReplayRecord<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.title_id = Some(cursor.read_lookback_string().context("Reading replay title ID")?.into());
+            let _author_version = cursor.read_u32().context("Reading replay author version")?;
+            this.author_login = Some(cursor.read_string().context("Reading replay author login")?.into());
+            this.author_nickname = Some(cursor.read_string().context("Reading replay author nickname")?.into());
+            this.author_zone = Some(cursor.read_string().context("Reading replay author zone")?.into());
+            this.author_extra_info = Some(cursor.read_string().context("Reading replay author extra info")?.into());
+            Ok(())
+        },
+    },
+
+    0x0305b000 CtnChallengeParameters {
+        author_score: Option<i32>,
+        bronze_time: Option<i32>,
+        silver_time: Option<i32>,
+        gold_time: Option<i32>,
+        author_time: Option<i32>,
+        map_type: Option<Cow<'node, str>>,
+        map_style: Option<Cow<'node, str>>,
+        is_validated: Option<bool>,
+        validation_ghost: Option<NodeRef<CtnGhost<'node>>>,
+        time_limit: Option<i32>,
+        tip: Option<Cow<'node, str>>,
+        tip1: Option<Cow<'node, str>>,
+        tip2: Option<Cow<'node, str>>,
+        tip3: Option<Cow<'node, str>>,
+        tip4: Option<Cow<'node, str>>,
+    } {
+        0x0305b001 => |this: &mut CtnChallengeParameters<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            tracing::trace!("tips");
+            this.tip1 = Some(cursor.read_string().context("Reading tip 1")?.into());
+            this.tip2 = Some(cursor.read_string().context("Reading tip 2")?.into());
+            this.tip3 = Some(cursor.read_string().context("Reading tip 3")?.into());
+            this.tip4 = Some(cursor.read_string().context("Reading tip 4")?.into());
+            Ok(())
+        },
+
+        0x0305b004 => |this: &mut CtnChallengeParameters<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            tracing::trace!("medal times");
+            this.bronze_time = negative_none(cursor.read_i32().context("Reading bronze time")?);
+            this.silver_time = negative_none(cursor.read_i32().context("Reading silver time")?);
+            this.gold_time = negative_none(cursor.read_i32().context("Reading gold time")?);
+            this.author_time = negative_none(cursor.read_i32().context("Reading author time")?);
+            let _unknown = cursor.read_u32().context("Reading unknown medal time parameter")?;
+            Ok(())
+        },
+
+        0x0305b008 => |this: &mut CtnChallengeParameters<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            tracing::trace!("stunt info");
+            this.time_limit = negative_none(cursor.read_i32().context("Reading time limit")?);
+            this.author_score = negative_none(cursor.read_i32().context("Reading author score")?);
+            Ok(())
+        },
+
+        0x0305b00a => |this: &mut CtnChallengeParameters<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            tracing::trace!("skippable medal times");
+            this.tip = Some(cursor.read_string().context("Reading skippable medal times tip")?.into());
+            this.bronze_time = negative_none(cursor.read_i32().context("Reading skippable bronze time")?);
+            this.silver_time = negative_none(cursor.read_i32().context("Reading skippable silver time")?);
+            this.gold_time = negative_none(cursor.read_i32().context("Reading skippable gold time")?);
+            this.author_time = negative_none(cursor.read_i32().context("Reading skippable author time")?);
+            this.time_limit = negative_none(cursor.read_i32().context("Reading skippable time limit")?);
+            this.author_score = negative_none(cursor.read_i32().context("Reading skippable author score")?);
+            Ok(())
+        },
+
+        0x0305b00d => |this: &mut CtnChallengeParameters<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            tracing::trace!("stunt info");
+            this.validation_ghost = cursor.expect_node_ref::<CtnGhost>().context("Reading validation ghost")?;
+            Ok(())
+        },
+    },
+
+    0x03092000 CtnGhost {
+        player_model: Option<Meta<'node>>,
+        race_time: Option<i32>,
+        respawns: Option<u32>,
+        stunt_score: Option<u32>,
+        light_trail_color: Option<[f32; 3]>,
+        checkpoints: Vec<GhostCheckpoint>,
+        uid: Option<Cow<'node, str>>,
+        login: Option<Cow<'node, str>>,
+        nickname: Option<Cow<'node, str>>,
+        avatar_name: Option<Cow<'node, str>>,
+        skin_pack_descs: Vec<FileRef<'node>>,
+        player_mobil_id: Option<Cow<'node, str>>,
+        game_version: Option<Cow<'node, str>>,
+        is_replaying: Option<bool>,
+        sample_data_size: Option<u32>,
+        #[derivative(Debug = "ignore")]
+        #[serde(with = "crate::base64_bytes::option")]
+        sample_data: Option<Cow<'node, [u8]>>,
+        events_duration: Option<u32>,
+        #[derivative(Debug = "ignore")]
+        inputs: Vec<GhostInput<'node>>,
+    } {
+        // CGameGhost: compressed sample stream
+        0x0303f005 => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.sample_data_size = Some(cursor.read_u32().context("Reading ghost sample data uncompressed size")?);
+            let compressed_size = cursor.read_u32().context("Reading ghost sample data compressed size")?;
+            this.sample_data = Some(cursor.read_bytes(compressed_size as usize).context("Reading ghost sample data")?.into());
+            Ok(())
+        },
+
+        // CGameGhost: replaying flag and compressed sample stream
+        0x0303f006 => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.is_replaying = Some(cursor.read_bool().context("Reading ghost is replaying")?);
+            this.sample_data_size = Some(cursor.read_u32().context("Reading ghost sample data uncompressed size")?);
+            let compressed_size = cursor.read_u32().context("Reading ghost sample data compressed size")?;
+            this.sample_data = Some(cursor.read_bytes(compressed_size as usize).context("Reading ghost sample data")?.into());
+            Ok(())
+        },
+
+        0x03092005 => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.race_time = negative_none(cursor.read_i32().context("Reading ghost race time")?);
+            Ok(())
+        },
+
+        0x03092008 => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.respawns = Some(cursor.read_u32().context("Reading ghost respawns")?);
+            Ok(())
+        },
+
+        0x03092009 => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.light_trail_color = Some(cursor.read_vec3().context("Reading ghost light trail color")?);
+            Ok(())
+        },
+
+        0x0309200a => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.stunt_score = Some(cursor.read_u32().context("Reading ghost stunt score")?);
+            Ok(())
+        },
+
+        0x0309200b => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let len = cursor.read_u32().context("Reading ghost number of checkpoints")?;
+            for i in 0..len {
+                this.checkpoints.push(GhostCheckpoint {
+                    time: negative_none(cursor.read_i32().with_context(|| format!("Reading ghost checkpoint {i} time"))?),
+                    stunt_score: cursor.read_i32().with_context(|| format!("Reading ghost checkpoint {i} stunt score"))?,
+                });
+            }
+            Ok(())
+        },
+
+        0x0309200c => |_this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _unknown = cursor.read_u32().context("Reading ghost unknown")?;
+            Ok(())
+        },
+
+        0x0309200e => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.uid = Some(cursor.read_lookback_string().context("Reading ghost UID")?.into());
+            Ok(())
+        },
+
+        0x0309200f => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.login = Some(cursor.read_string().context("Reading ghost login")?.into());
+            Ok(())
+        },
+
+        0x03092010 => |_this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _unknown = cursor.read_lookback_string().context("Reading ghost unknown ID")?;
+            Ok(())
+        },
+
+        0x03092012 => |_this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _unknown = cursor.read_u32().context("Reading ghost unknown 1")?;
+            let _unknown = cursor.read_u128().context("Reading ghost unknown 2")?;
+            Ok(())
+        },
+
+        0x03092015 => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.player_mobil_id = Some(cursor.read_lookback_string().context("Reading ghost player mobil ID")?.into());
+            Ok(())
+        },
+
+        0x03092017 => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let len = cursor.read_u32().context("Reading ghost number of skin pack descs")?;
+            for i in 0..len {
+                this.skin_pack_descs.push(cursor.read_file_ref().with_context(|| format!("Reading ghost skin pack desc {i}"))?);
+            }
+            this.nickname = Some(cursor.read_string().context("Reading ghost nickname")?.into());
+            this.avatar_name = Some(cursor.read_string().context("Reading ghost avatar name")?.into());
+            Ok(())
+        },
+
+        0x03092018 => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.player_model = Some(cursor.read_meta().context("Reading ghost player model")?);
+            Ok(())
+        },
+
+        0x03092019 => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let events_duration = cursor.read_u32().context("Reading ghost events duration")?;
+            this.events_duration = Some(events_duration);
+            if events_duration == 0 {
+                return Ok(());
+            }
+
+            let _unknown = cursor.read_u32().context("Reading ghost events unknown 1")?;
+            let num_control_names = cursor.read_u32().context("Reading ghost number of control names")?;
+            let mut control_names = Vec::new();
+            for i in 0..num_control_names {
+                control_names.push(cursor.read_lookback_string().with_context(|| format!("Reading ghost control name {i}"))?);
+            }
+
+            let num_entries = cursor.read_u32().context("Reading ghost number of control entries")?;
+            let _unknown = cursor.read_u32().context("Reading ghost events unknown 2")?;
+            for i in 0..num_entries {
+                let time = cursor.read_u32().with_context(|| format!("Reading ghost control entry {i} time"))?;
+                let name_index = cursor.read_u8().with_context(|| format!("Reading ghost control entry {i} name index"))?;
+                let data = cursor.read_u32().with_context(|| format!("Reading ghost control entry {i} data"))?;
+                this.inputs.push(GhostInput {
+                    time,
+                    name: control_names.get(name_index as usize).copied().unwrap_or_default().into(),
+                    data,
+                });
+            }
+
+            Ok(())
+        },
+
+        0x0309201c => |_this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _unknown = cursor.read_bytes(32).context("Reading ghost unknown")?;
+            Ok(())
+        },
+
+        // validation info
+        0x03092025 => |this: &mut CtnGhost<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading ghost validation version")?;
+            this.game_version = Some(cursor.read_string().context("Reading ghost game version")?.into());
+            Ok(())
+        },
+    },
+
+    0x2e009000 WaypointSpecialProperty {
+        order: Option<u32>,
+        spawn: Option<u32>,
+        tag: Option<Cow<'node, str>>,
+    } {
+        0x2e009000 => |this: &mut WaypointSpecialProperty<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let version = cursor.read_u32().context("Reading waypoint data version")?;
+
+            if version == 1 {
+                this.spawn = Some(cursor.read_u32().context("Reading waypoint spawn")?);
+                this.order = Some(cursor.read_u32().context("Reading waypoint order")?);
+            } else {
+                this.tag = Some(cursor.read_string().context("Reading waypoint tag")?.into());
+                this.order = Some(cursor.read_u32().context("Reading waypoint order")?);
+            }
+
+            Ok(())
+        },
+    },
+
+    0x03101000 CtnAnchoredObject {
+        item_model: Option<Meta<'node>>,
+        yaw_pitch_roll: Option<[f32; 3]>,
+        block_unit_coord: Option<[u8; 3]>,
+        anchor_tree_id: Option<Cow<'node, str>>,
+        position: Option<[f32; 3]>,
+        waypoint_special_property: Option<WaypointSpecialProperty<'node>>,
+        flags: Option<u16>,
+        pivot_position: Option<[f32; 3]>,
+        scale: Option<f32>,
+        color: Option<DifficultyColor>,
+        animation_phase_offset: Option<PhaseOffset>,
+        skin: Option<FileRef<'node>>,
+        foreground_skin: Option<FileRef<'node>>,
+    } {
+        0x03101002 => |this: &mut CtnAnchoredObject<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            read_anchored_object(cursor, this)
+        },
+    },
+
+    0x03059000 CtnBlockSkin {
+        foreground_pack_desc: Option<FileRef<'node>>,
+        pack_desc: Option<FileRef<'node>>,
+        parent_pack_desc: Option<FileRef<'node>>,
+        text: Option<Cow<'node, str>>,
+    } {
+        // text
+        0x03059000 => |this: &mut CtnBlockSkin<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.text = Some(cursor.read_string().context("Reading block text")?.into());
+            let _unknown = cursor.read_string().context("Reading block text unknown")?;
+            Ok(())
+        },
+
+        // skin
+        0x03059001 => |this: &mut CtnBlockSkin<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.text = Some(cursor.read_string().context("Reading block skin text")?.into());
             this.pack_desc = Some(cursor.read_file_ref().context("Reading block skin pack desc")?);
             Ok(())
         },
 
         // skin and parent skin
         0x03059002 => |this: &mut CtnBlockSkin<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            this.text = Some(cursor.read_string().context("Reading block skin/parent text data")?);
+            this.text = Some(cursor.read_string().context("Reading block skin/parent text data")?.into());
             this.pack_desc = Some(cursor.read_file_ref().context("Reading block skin/parent pack desc")?);
             this.parent_pack_desc = Some(cursor.read_file_ref().context("Reading block skin/parent parent pack desc")?);
             Ok(())
@@ -600,7 +1577,7 @@ parser!(
 
         // secondary skin
         0x03059003 => |this: &mut CtnBlockSkin<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            let _version = cursor.read_u32::<LE>().context("Reading secondary skin version")?;
+            let _version = cursor.read_u32().context("Reading secondary skin version")?;
             this.foreground_pack_desc = Some(cursor.read_file_ref().context("Reading secondary skin foreground pack desc")?);
             Ok(())
         },
@@ -610,7 +1587,7 @@ parser!(
         block_set: Vec<Meta<'node>>,
     } {
         0x0301b000 => |this: &mut CtnCollectorList<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
-            let len = cursor.read_u32::<LE>().context("Reading block set length")?;
+            let len = cursor.read_u32().context("Reading block set length")?;
             tracing::trace!("block set: {} blocks", len);
             for i in 0..len {
                 this.block_set.push(
@@ -621,5 +1598,875 @@ parser!(
             }
             Ok(())
         }
-    }
+    },
+
+    0x0307a000 CtnMediaClipGroup {
+        clips: Vec<NodeRef<CtnMediaClip<'node>>>,
+        /// One for each clip
+        triggers: Vec<MediaTrigger>,
+    } {
+        0x0307a003 => |this: &mut CtnMediaClipGroup<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _list_version = cursor.read_u32().context("Reading clip list version")?;
+            let num_clips = cursor.read_u32().context("Reading number of clips")?;
+            for i in 0..num_clips {
+                if let Some(clip) = cursor.expect_node_ref::<CtnMediaClip>().with_context(|| format!("Reading clip {i}"))? {
+                    this.clips.push(clip);
+                }
+            }
+
+            let num_triggers = cursor.read_u32().context("Reading number of triggers")?;
+            for i in 0..num_triggers {
+                for _ in 0..4 {
+                    let _unknown = cursor.read_u32().with_context(|| format!("Reading trigger {i} unknown"))?;
+                }
+                let condition = cursor.read_u32().with_context(|| format!("Reading trigger {i} condition"))?;
+                let condition_value = cursor.read_f32().with_context(|| format!("Reading trigger {i} condition value"))?;
+                let num_coords = cursor.read_u32().with_context(|| format!("Reading trigger {i} number of coords"))?;
+                let coords = (0..num_coords)
+                    .map(|_| cursor.read_int3())
+                    .collect::<Result<_, _>>()
+                    .with_context(|| format!("Reading trigger {i} coords"))?;
+                this.triggers.push(MediaTrigger { coords, condition, condition_value });
+            }
+            Ok(())
+        },
+    },
+
+    0x03079000 CtnMediaClip {
+        name: Option<Cow<'node, str>>,
+        tracks: Vec<NodeRef<CtnMediaTrack<'node>>>,
+        stop_when_leave: Option<bool>,
+        stop_when_respawn: Option<bool>,
+    } {
+        0x0307900d => |this: &mut CtnMediaClip<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading clip version")?;
+            let _list_version = cursor.read_u32().context("Reading track list version")?;
+            let num_tracks = cursor.read_u32().context("Reading number of tracks")?;
+            for i in 0..num_tracks {
+                if let Some(track) = cursor.expect_node_ref::<CtnMediaTrack>().with_context(|| format!("Reading track {i}"))? {
+                    this.tracks.push(track);
+                }
+            }
+            this.name = Some(cursor.read_string().context("Reading clip name")?.into());
+            this.stop_when_leave = Some(cursor.read_bool().context("Reading clip stop when leave")?);
+            let _unknown = cursor.read_bool().context("Reading clip unknown 1")?;
+            this.stop_when_respawn = Some(cursor.read_bool().context("Reading clip stop when respawn")?);
+            let _unknown = cursor.read_string().context("Reading clip unknown 2")?;
+            let _unknown = cursor.read_f32().context("Reading clip unknown 3")?;
+            let _local_player_clip_ent_index = cursor.read_i32().context("Reading clip local player entity index")?;
+            Ok(())
+        },
+    },
+
+    0x03078000 CtnMediaTrack {
+        name: Option<Cow<'node, str>>,
+        /// MediaTracker blocks, like CtnMediaBlockCameraGame or CtnMediaBlockText
+        blocks: Vec<CGame<'node>>,
+        is_keep_playing: Option<bool>,
+        is_read_only: Option<bool>,
+        is_cycling: Option<bool>,
+    } {
+        0x03078001 => |this: &mut CtnMediaTrack<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.name = Some(cursor.read_string().context("Reading track name")?.into());
+            let _list_version = cursor.read_u32().context("Reading block list version")?;
+            let num_blocks = cursor.read_u32().context("Reading number of blocks")?;
+            for i in 0..num_blocks {
+                if let Some(block) = cursor.read_node_ref().with_context(|| format!("Reading block {i}"))? {
+                    this.blocks.extend(block.into_node());
+                }
+            }
+            let _unknown = cursor.read_u32().context("Reading track unknown")?;
+            Ok(())
+        },
+
+        0x03078005 => |this: &mut CtnMediaTrack<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let version = cursor.read_u32().context("Reading track flags version")?;
+            this.is_keep_playing = Some(cursor.read_bool().context("Reading track is keep playing")?);
+            this.is_read_only = Some(cursor.read_bool().context("Reading track is read only")?);
+            this.is_cycling = Some(cursor.read_bool().context("Reading track is cycling")?);
+            if version >= 1 {
+                let _unknown = cursor.read_f32().context("Reading track unknown 1")?;
+                let _unknown = cursor.read_f32().context("Reading track unknown 2")?;
+            }
+            Ok(())
+        },
+    },
+
+    0x03084000 CtnMediaBlockCameraGame {
+        start: Option<f32>,
+        end: Option<f32>,
+    } {
+        0x03084007 => |this: &mut CtnMediaBlockCameraGame<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let version = cursor.read_u32().context("Reading game camera version")?;
+            this.start = Some(cursor.read_f32().context("Reading game camera start")?);
+            this.end = Some(cursor.read_f32().context("Reading game camera end")?);
+            // camera placement and settings, which are only known for this version
+            if version != 4 {
+                return skip_rest_of_chunk(cursor, 0x03084007).context("Skipping game camera settings");
+            }
+            let _camera = cursor.read_u32().context("Reading game camera type")?;
+            let _target = cursor.read_i32().context("Reading game camera target")?;
+            let _position = cursor.read_vec3().context("Reading game camera position")?;
+            let _pitch_yaw_roll = cursor.read_vec3().context("Reading game camera rotation")?;
+            let _fov = cursor.read_f32().context("Reading game camera field of view")?;
+            let _unknown = cursor.read_f32().context("Reading game camera unknown 1")?;
+            let _unknown = cursor.read_u32().context("Reading game camera unknown 2")?;
+            let _near_clip_plane = cursor.read_f32().context("Reading game camera near clip plane")?;
+            let _far_clip_plane = cursor.read_f32().context("Reading game camera far clip plane")?;
+            for i in 0..3 {
+                let _unknown = cursor.read_bool().with_context(|| format!("Reading game camera flag {i}"))?;
+            }
+            let _unknown = cursor.read_f32().context("Reading game camera unknown 3")?;
+            let _unknown = cursor.read_u32().context("Reading game camera unknown 4")?;
+            Ok(())
+        },
+    },
+
+    0x030a8000 CtnMediaBlockText {
+        start: Option<f32>,
+        end: Option<f32>,
+        text: Option<Cow<'node, str>>,
+        effect: Option<NodeRef<ControlEffectSimi<'node>>>,
+        color: Option<[f32; 3]>,
+    } {
+        0x030a8001 => |this: &mut CtnMediaBlockText<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.text = Some(cursor.read_string().context("Reading media text")?.into());
+            this.effect = cursor.expect_node_ref::<ControlEffectSimi>().context("Reading media text effect")?;
+            (this.start, this.end) = this.effect.as_ref().and_then(NodeRef::local).map_or((None, None), ControlEffectSimi::time_range);
+            Ok(())
+        },
+
+        0x030a8002 => |this: &mut CtnMediaBlockText<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.color = Some(cursor.read_vec3().context("Reading media text color")?);
+            Ok(())
+        },
+    },
+
+    0x030a5000 CtnMediaBlockImage {
+        start: Option<f32>,
+        end: Option<f32>,
+        effect: Option<NodeRef<ControlEffectSimi<'node>>>,
+        image: Option<FileRef<'node>>,
+    } {
+        0x030a5000 => |this: &mut CtnMediaBlockImage<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.effect = cursor.expect_node_ref::<ControlEffectSimi>().context("Reading media image effect")?;
+            (this.start, this.end) = this.effect.as_ref().and_then(NodeRef::local).map_or((None, None), ControlEffectSimi::time_range);
+            this.image = Some(cursor.read_file_ref().context("Reading media image file")?);
+            Ok(())
+        },
+    },
+
+    0x07010000 ControlEffectSimi {
+        keys: Vec<SimiKey>,
+        centered: Option<bool>,
+        color_blend_mode: Option<u32>,
+        is_continuous_effect: Option<bool>,
+        is_interpolated: Option<bool>,
+    } {
+        0x07010005 => |this: &mut ControlEffectSimi<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let num_keys = cursor.read_u32().context("Reading number of effect keys")?;
+            for i in 0..num_keys {
+                let key = (|| -> Result<SimiKey, GbxError> {
+                    let key = SimiKey {
+                        time: cursor.read_f32()?,
+                        position: cursor.read_vec2()?,
+                        rotation: cursor.read_f32()?,
+                        scale: cursor.read_vec2()?,
+                        opacity: cursor.read_f32()?,
+                        depth: cursor.read_f32()?,
+                    };
+                    for _ in 0..4 {
+                        let _unknown = cursor.read_f32()?;
+                    }
+                    Ok(key)
+                })().with_context(|| format!("Reading effect key {i}"))?;
+                this.keys.push(key);
+            }
+            this.centered = Some(cursor.read_bool().context("Reading effect centered")?);
+            this.color_blend_mode = Some(cursor.read_u32().context("Reading effect color blend mode")?);
+            this.is_continuous_effect = Some(cursor.read_bool().context("Reading effect is continuous")?);
+            this.is_interpolated = Some(cursor.read_bool().context("Reading effect is interpolated")?);
+            Ok(())
+        },
+    },
+
+    0x0329f000 CtnMediaBlockEntity {
+        record_data: Option<NodeRef<PlugEntRecordData<'node>>>,
+    } {
+        0x0329f000 => |this: &mut CtnMediaBlockEntity<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let version = cursor.read_u32().context("Reading media entity version")?;
+            this.record_data = cursor.expect_node_ref::<PlugEntRecordData>().context("Reading media entity record data")?;
+            // skins, keys and the ghost's name, which are only known for this version
+            if version != 11 {
+                return skip_rest_of_chunk(cursor, 0x0329f000).context("Skipping media entity settings");
+            }
+            let _start_offset = cursor.read_u32().context("Reading media entity start offset")?;
+            let num_notice_records = cursor.read_u32().context("Reading media entity number of notice records")?;
+            for i in 0..num_notice_records {
+                let _notice_record = cursor.read_u32().with_context(|| format!("Reading media entity notice record {i}"))?;
+            }
+            for i in 0..4 {
+                let _unknown = cursor.read_bool().with_context(|| format!("Reading media entity flag {i}"))?;
+            }
+            let _unknown = cursor.read_u32().context("Reading media entity unknown 1")?;
+            let _player_model = cursor.read_meta().context("Reading media entity player model")?;
+            let _unknown = cursor.read_vec3().context("Reading media entity unknown 2")?;
+            let num_skins = cursor.read_u32().context("Reading media entity number of skins")?;
+            for i in 0..num_skins {
+                let _skin = cursor.read_file_ref().with_context(|| format!("Reading media entity skin {i}"))?;
+            }
+            let _unknown = cursor.read_u32().context("Reading media entity unknown 3")?;
+            let _unknown = cursor.read_u32().context("Reading media entity unknown 4")?;
+            // lights, and other settings over time
+            let _key_times = read_key_times(cursor, 6).context("Reading media entity keys")?;
+            let _ghost_name = cursor.read_string().context("Reading media entity ghost name")?;
+            let _race_time = cursor.read_u32().context("Reading media entity race time")?;
+            let _unknown = cursor.read_u32().context("Reading media entity unknown 5")?;
+            let _unknown = cursor.read_u32().context("Reading media entity unknown 6")?;
+            Ok(())
+        },
+
+        0x0329f002 => |_this: &mut CtnMediaBlockEntity<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _unknown = cursor.read_u32().context("Reading media entity unknown")?;
+            Ok(())
+        },
+    },
+
+    0x0911f000 PlugEntRecordData {
+        uncompressed_size: Option<u32>,
+        #[derivative(Debug = "ignore")]
+        #[serde(with = "crate::base64_bytes::option")]
+        compressed_data: Option<Cow<'node, [u8]>>,
+    } {
+        0x0911f000 => |this: &mut PlugEntRecordData<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading record data version")?;
+            this.uncompressed_size = Some(cursor.read_u32().context("Reading record data uncompressed size")?);
+            let compressed_size = cursor.read_u32().context("Reading record data compressed size")?;
+            this.compressed_data = Some(cursor.read_bytes(compressed_size as usize).context("Reading record data")?.into());
+            Ok(())
+        },
+    },
+
+    0x03085000 CtnMediaBlockTime {
+        start: Option<f32>,
+        end: Option<f32>,
+    } {
+        0x03085000 => |this: &mut CtnMediaBlockTime<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            // time value and tangent
+            let times = read_key_times(cursor, 2).context("Reading time speed keys")?;
+            (this.start, this.end) = (times.first().copied(), times.last().copied());
+            Ok(())
+        },
+    },
+
+    0x030ab000 CtnMediaBlockTransitionFade {
+        start: Option<f32>,
+        end: Option<f32>,
+        color: Option<[f32; 3]>,
+    } {
+        0x030ab000 => |this: &mut CtnMediaBlockTransitionFade<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            // opacity
+            let times = read_key_times(cursor, 1).context("Reading fade keys")?;
+            (this.start, this.end) = (times.first().copied(), times.last().copied());
+            this.color = Some(cursor.read_vec3().context("Reading fade color")?);
+            let _unknown = cursor.read_f32().context("Reading fade unknown")?;
+            Ok(())
+        },
+    },
+
+    0x030a4000 CtnMediaBlockCameraEffectShake {
+        start: Option<f32>,
+        end: Option<f32>,
+    } {
+        0x030a4000 => |this: &mut CtnMediaBlockCameraEffectShake<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            // intensity and speed
+            let times = read_key_times(cursor, 2).context("Reading camera shake keys")?;
+            (this.start, this.end) = (times.first().copied(), times.last().copied());
+            Ok(())
+        },
+    },
+
+    0x2e002000 ItemModel {
+        item_info: Option<Meta<'node>>,
+        page_name: Option<Cow<'node, str>>,
+        catalog_position: Option<u16>,
+        name: Option<Cow<'node, str>>,
+        description: Option<Cow<'node, str>>,
+        #[derivative(Debug = "ignore")]
+        icon: Option<ItemIcon<'node>>,
+        /// Path of the image the icon was made from, when it wasn't rendered automatically
+        icon_path: Option<Cow<'node, str>>,
+        item_type: Option<ItemType>,
+        waypoint_type: Option<WaypointType>,
+        kind: Option<ItemModelKind>,
+        /// Class of the node the item is made of, like CGameCommonItemEntityModel for mesh items
+        entity_model_class: Option<u32>,
+        placement: Option<NodeRef<ItemPlacementParam<'node>>>,
+    } {
+        // collector, in the header
+        0x2e001003 => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let collector = read_collector_info(cursor)?;
+            this.item_info = Some(collector.info);
+            this.page_name = Some(collector.page_name.into());
+            this.catalog_position = collector.catalog_position;
+            this.name = collector.name.map(Into::into);
+            Ok(())
+        },
+
+        // icon, in the header
+        0x2e001004 => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.icon = Some(read_collector_icon(cursor)?);
+            Ok(())
+        },
+
+        // item type, in the header
+        0x2e002000 => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let item_type = cursor.read_u32().context("Reading item type")?;
+            this.item_type = u8::try_from(item_type).ok().and_then(ItemType::from_repr);
+            Ok(())
+        },
+
+        0x2e001009 => |_this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            skip_collector_page(cursor)
+        },
+
+        0x2e00100b => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.item_info = Some(cursor.read_meta().context("Reading item info")?);
+            Ok(())
+        },
+
+        0x2e00100c => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.name = Some(cursor.read_string().context("Reading item name")?.into());
+            Ok(())
+        },
+
+        0x2e00100d => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.description = Some(cursor.read_string().context("Reading item description")?.into());
+            Ok(())
+        },
+
+        0x2e001010 => |_this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            skip_collector_skin(cursor)
+        },
+
+        0x2e001011 => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.catalog_position = Some(read_collector_catalog(cursor)?);
+            Ok(())
+        },
+
+        0x2e001012 => |_this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            for _ in 0..4 {
+                let _unknown = cursor.read_u32().context("Reading collector unknown")?;
+            }
+            Ok(())
+        },
+
+        // nadeo skins
+        0x2e002008 => |_this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let num_skins = cursor.read_u32().context("Reading number of skins")?;
+            for i in 0..num_skins {
+                let _skin = cursor.read_node_ref().with_context(|| format!("Reading skin {i}"))?;
+            }
+            Ok(())
+        },
+
+        0x2e002009 => |_this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading cameras version")?;
+            let num_cameras = cursor.read_u32().context("Reading number of cameras")?;
+            for i in 0..num_cameras {
+                let _camera = cursor.read_node_ref().with_context(|| format!("Reading camera {i}"))?;
+            }
+            Ok(())
+        },
+
+        0x2e00200c => |_this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _race_interface = cursor.read_node_ref().context("Reading race interface")?;
+            Ok(())
+        },
+
+        0x2e002012 => |_this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _ground_point = cursor.read_vec3().context("Reading ground point")?;
+            let _painter_ground_margin = cursor.read_f32().context("Reading painter ground margin")?;
+            let _orbital_center_height = cursor.read_f32().context("Reading orbital center height")?;
+            let _orbital_radius_base = cursor.read_f32().context("Reading orbital radius base")?;
+            let _orbital_preview_angle = cursor.read_f32().context("Reading orbital preview angle")?;
+            Ok(())
+        },
+
+        0x2e002015 => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let item_type = cursor.read_u32().context("Reading item type")?;
+            this.item_type = u8::try_from(item_type).ok().and_then(ItemType::from_repr);
+            Ok(())
+        },
+
+        0x2e002019 => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading entity model version")?;
+            let _phy_model = cursor.read_node_ref().context("Reading physics model")?;
+            let _vis_model = cursor.read_node_ref().context("Reading visual model")?;
+            let _unknown = cursor.read_node_ref().context("Reading entity model unknown 1")?;
+            let _unknown = cursor.read_u32().context("Reading entity model unknown 2")?;
+            let _unknown = cursor.read_u32().context("Reading entity model unknown 3")?;
+            let _unknown = cursor.read_node_ref().context("Reading entity model unknown 4")?;
+
+            // the model holds the whole mesh or block, and is usually of a class that isn't
+            // parsed, which is skipped to its end marker instead
+            match cursor.peek_new_node_class().context("Reading entity model")? {
+                Some(class_id) if !CGame::knows_class(class_id, cursor.registry().as_deref()) => {
+                    let _index = cursor.read_i32().context("Reading entity model index")?;
+                    let _class_id = cursor.read_u32().context("Reading entity model class ID")?;
+                    this.entity_model_class = Some(class_id);
+                    cursor.force_skip_node(0x2e002019, 2, 0x2e00201a).context("Skipping entity model")?;
+                }
+                _ => {
+                    let model = cursor.read_node_ref().context("Reading entity model")?;
+                    // external nodes don't say what class they are
+                    this.entity_model_class = model.map(|model| model.class_id()).filter(|&class_id| class_id != 0);
+                }
+            }
+            this.kind = this.entity_model_class.map(|class_id| {
+                if class_id == BLOCK_ITEM_CLASS_ID { ItemModelKind::Block } else { ItemModelKind::Mesh }
+            });
+            let _unknown = cursor.read_node_ref().context("Reading entity model unknown 5")?;
+            let _unknown = cursor.read_node_ref().context("Reading entity model unknown 6")?;
+            Ok(())
+        },
+
+        0x2e00201a => |_this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _default_camera = cursor.read_node_ref().context("Reading default camera")?;
+            Ok(())
+        },
+
+        0x2e00201c => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading placement version")?;
+            this.placement = cursor.expect_node_ref::<ItemPlacementParam>().context("Reading placement")?;
+            Ok(())
+        },
+
+        0x2e00201e => |_this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let version = cursor.read_u32().context("Reading archetype version")?;
+            let _archetype = cursor.read_string().context("Reading archetype")?;
+            let _unknown = cursor.read_i32().context("Reading archetype unknown 1")?;
+            let _skin_directory = cursor.read_string().context("Reading custom skin directory")?;
+            if version >= 7 {
+                let _unknown = cursor.read_i32().context("Reading archetype unknown 2")?;
+            }
+            Ok(())
+        },
+
+        0x2e00201f => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let version = cursor.read_u32().context("Reading waypoint version")?;
+            let waypoint_type = cursor.read_u32().context("Reading waypoint type")?;
+            this.waypoint_type = u8::try_from(waypoint_type).ok().and_then(WaypointType::from_repr);
+            let _disable_lightmap = cursor.read_bool().context("Reading disable lightmap")?;
+            if version >= 10 {
+                let _unknown = cursor.read_i32().context("Reading waypoint unknown 1")?;
+            }
+            if version >= 11 {
+                let _unknown = cursor.read_u8().context("Reading waypoint unknown 2")?;
+            }
+            if version >= 12 {
+                let _unknown = cursor.read_i32().context("Reading waypoint unknown 3")?;
+                let _unknown = cursor.read_i32().context("Reading waypoint unknown 4")?;
+            }
+            Ok(())
+        },
+
+        0x2e002020 => |this: &mut ItemModel<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let version = cursor.read_u32().context("Reading icon path version")?;
+            let icon_path = cursor.read_string().context("Reading icon path")?;
+            this.icon_path = Some(icon_path.into()).filter(|path: &Cow<str>| !path.is_empty());
+            if version >= 3 {
+                let _unknown = cursor.read_u8().context("Reading icon path unknown")?;
+            }
+            Ok(())
+        },
+    },
+
+    0x2e020000 ItemPlacementParam {
+        flags: Option<u16>,
+        cube_center: Option<[f32; 3]>,
+        cube_size: Option<f32>,
+        grid_snap_h_step: Option<f32>,
+        grid_snap_v_step: Option<f32>,
+        grid_snap_h_offset: Option<f32>,
+        grid_snap_v_offset: Option<f32>,
+        fly_step: Option<f32>,
+        fly_offset: Option<f32>,
+        pivot_snap_distance: Option<f32>,
+        pivot_positions: Vec<[f32; 3]>,
+        switch_pivot_manually: Option<bool>,
+    } {
+        0x2e020000 => |this: &mut ItemPlacementParam<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _unknown = cursor.read_u32().context("Reading placement unknown")?;
+            this.flags = Some(cursor.read_u16().context("Reading placement flags")?);
+            this.cube_center = Some(cursor.read_vec3().context("Reading cube center")?);
+            this.cube_size = Some(cursor.read_f32().context("Reading cube size")?);
+            this.grid_snap_h_step = Some(cursor.read_f32().context("Reading grid snap horizontal step")?);
+            this.grid_snap_v_step = Some(cursor.read_f32().context("Reading grid snap vertical step")?);
+            this.grid_snap_h_offset = Some(cursor.read_f32().context("Reading grid snap horizontal offset")?);
+            this.grid_snap_v_offset = Some(cursor.read_f32().context("Reading grid snap vertical offset")?);
+            this.fly_step = Some(cursor.read_f32().context("Reading fly step")?);
+            this.fly_offset = Some(cursor.read_f32().context("Reading fly offset")?);
+            this.pivot_snap_distance = Some(cursor.read_f32().context("Reading pivot snap distance")?);
+            Ok(())
+        },
+
+        0x2e020001 => |this: &mut ItemPlacementParam<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let num_pivots = cursor.read_u32().context("Reading number of pivots")?;
+            for i in 0..num_pivots {
+                this.pivot_positions.push(cursor.read_vec3().with_context(|| format!("Reading pivot {i}"))?);
+            }
+            this.switch_pivot_manually = Some(cursor.read_bool().context("Reading switch pivot manually")?);
+            Ok(())
+        },
+    },
+
+    0x0310d000 CtnMacroBlockInfo {
+        macroblock_info: Option<Meta<'node>>,
+        page_name: Option<Cow<'node, str>>,
+        catalog_position: Option<u16>,
+        name: Option<Cow<'node, str>>,
+        description: Option<Cow<'node, str>>,
+        #[derivative(Debug = "ignore")]
+        icon: Option<ItemIcon<'node>>,
+        /// Positions are relative to the macroblock
+        #[derivative(Debug = "ignore")]
+        blocks: Vec<Block<'node>>,
+        /// Positions are relative to the macroblock. Unlike in maps, custom items aren't embedded
+        /// in the file: `item_model` is the path of each one under the player's Items folder.
+        #[derivative(Debug = "ignore")]
+        items: Vec<CtnAnchoredObject<'node>>,
+    } {
+        // collector, in the header
+        0x2e001003 => |this: &mut CtnMacroBlockInfo<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let collector = read_collector_info(cursor)?;
+            this.macroblock_info = Some(collector.info);
+            this.page_name = Some(collector.page_name.into());
+            this.catalog_position = collector.catalog_position;
+            this.name = collector.name.map(Into::into);
+            Ok(())
+        },
+
+        // icon, in the header
+        0x2e001004 => |this: &mut CtnMacroBlockInfo<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.icon = Some(read_collector_icon(cursor)?);
+            Ok(())
+        },
+
+        0x2e001009 => |_this: &mut CtnMacroBlockInfo<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            skip_collector_page(cursor)
+        },
+
+        0x2e00100b => |this: &mut CtnMacroBlockInfo<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.macroblock_info = Some(cursor.read_meta().context("Reading macroblock info")?);
+            Ok(())
+        },
+
+        0x2e00100c => |this: &mut CtnMacroBlockInfo<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.name = Some(cursor.read_string().context("Reading macroblock name")?.into());
+            Ok(())
+        },
+
+        0x2e00100d => |this: &mut CtnMacroBlockInfo<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.description = Some(cursor.read_string().context("Reading macroblock description")?.into());
+            Ok(())
+        },
+
+        0x2e001010 => |_this: &mut CtnMacroBlockInfo<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            skip_collector_skin(cursor)
+        },
+
+        0x2e001011 => |this: &mut CtnMacroBlockInfo<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            this.catalog_position = Some(read_collector_catalog(cursor)?);
+            Ok(())
+        },
+
+        0x2e001012 => |_this: &mut CtnMacroBlockInfo<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            for _ in 0..4 {
+                let _unknown = cursor.read_u32().context("Reading collector unknown")?;
+            }
+            Ok(())
+        },
+
+        0x0310d000 => |this: &mut CtnMacroBlockInfo<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading block spawns version")?;
+            let num_blocks = cursor.read_u32().context("Reading number of blocks")?;
+            for i in 0..num_blocks as usize {
+                let _spawn_version = cursor.read_u32().with_context(|| format!("Reading block {i} version"))?;
+                let model = cursor.read_meta().with_context(|| format!("Reading block {i} model"))?;
+                let direction = cursor.read_u32().with_context(|| format!("Reading block {i} direction"))?;
+                // relative to the macroblock, so always within a map's size
+                let position = cursor.read_int3().with_context(|| format!("Reading block {i} position"))?;
+                let position = read_block_coords(position).with_context(|| format!("Reading block {i} position"))?;
+                let flags = cursor.read_u32().with_context(|| format!("Reading block {i} flags"))?;
+
+                // like in maps, nothing that depends on the flags follows
+                if flags == 0xffff_ffff {
+                    continue;
+                }
+
+                let direction = u8::try_from(direction).ok().and_then(Direction::from_repr);
+                let mut block = Block::new(model.id, direction, position, flags);
+                if block.is_free {
+                    block.free_position = Some(cursor.read_vec3().with_context(|| format!("Reading block {i} free position"))?);
+                    block.free_pitch_yaw_roll = Some(cursor.read_vec3().with_context(|| format!("Reading block {i} free rotation"))?);
+                }
+                read_block_extras(cursor, &mut block, i)?;
+                this.blocks.push(block);
+            }
+            Ok(())
+        },
+
+        0x0310d00e => |this: &mut CtnMacroBlockInfo<'node>, cursor: &mut BodyCursor<'node>| -> Result<(), GbxError> {
+            let _version = cursor.read_u32().context("Reading object spawns version")?;
+            let num_items = cursor.read_u32().context("Reading number of items")?;
+            for i in 0..num_items {
+                let mut item = CtnAnchoredObject::default();
+                read_anchored_object(cursor, &mut item).with_context(|| format!("Reading item {i}"))?;
+                this.items.push(item);
+            }
+            Ok(())
+        },
+    },
 );
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Block<'node> {
+    pub name: Cow<'node, str>,
+    pub direction: Option<Direction>,
+    pub position: [u8; 3],
+    pub flags: u32,
+    pub is_ground: bool,
+    pub is_ghost: bool,
+    pub is_free: bool,
+    pub author: Option<Cow<'node, str>>,
+    pub skin: Option<NodeRef<CtnBlockSkin<'node>>>,
+    pub waypoint_special_property: Option<NodeRef<WaypointSpecialProperty<'node>>>,
+    /// Where a free block in a macroblock is, since `position` doesn't place it
+    pub free_position: Option<[f32; 3]>,
+    pub free_pitch_yaw_roll: Option<[f32; 3]>,
+}
+
+impl<'node> Block<'node> {
+    fn new(
+        name: Cow<'node, str>,
+        direction: Option<Direction>,
+        position: [u8; 3],
+        flags: u32,
+    ) -> Self {
+        Block {
+            name,
+            direction,
+            position,
+            flags,
+            is_ground: (flags & 0x1000) != 0,
+            is_ghost: (flags & 0x1000_0000) != 0,
+            is_free: (flags & 0x2000_0000) != 0,
+            author: None,
+            skin: None,
+            waypoint_special_property: None,
+            free_position: None,
+            free_pitch_yaw_roll: None,
+        }
+    }
+}
+
+/// Block coordinates that are stored as u32s, which have to fit in a map
+fn read_block_coords(coords: [u32; 3]) -> Result<[u8; 3], GbxError> {
+    let mut position = [0; 3];
+    for (coord, value) in position.iter_mut().zip(coords) {
+        *coord = u8::try_from(value).map_err(|_| GbxErrorInner::InvalidBlockCoord(value))?;
+    }
+    Ok(position)
+}
+
+impl IntoOwned for Block<'_> {
+    type Owned = Block<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Block {
+            name: IntoOwned::into_owned(self.name),
+            author: IntoOwned::into_owned(self.author),
+            skin: IntoOwned::into_owned(self.skin),
+            waypoint_special_property: IntoOwned::into_owned(self.waypoint_special_property),
+            ..self
+        }
+    }
+}
+
+/// What a map needs an external file for
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum DependencyKind {
+    ModPack,
+    Music,
+    BlockSkin,
+    ItemSkin,
+    /// Only listed in the XML header
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MapDependency<'node> {
+    pub kind: DependencyKind,
+    pub file: FileRef<'node>,
+}
+
+impl CtnChallenge<'_> {
+    /// Every external file the map refers to, from both the body and the XML header
+    pub fn dependencies(&self) -> Vec<MapDependency<'_>> {
+        let block_skins = self
+            .blocks
+            .iter()
+            .filter_map(|block| block.skin.as_ref()?.local())
+            .flat_map(|skin| {
+                [
+                    &skin.pack_desc,
+                    &skin.parent_pack_desc,
+                    &skin.foreground_pack_desc,
+                ]
+            })
+            .map(|file| (DependencyKind::BlockSkin, file));
+        let item_skins = self
+            .items
+            .iter()
+            .flat_map(|item| [&item.skin, &item.foreground_skin])
+            .map(|file| (DependencyKind::ItemSkin, file));
+
+        let mut dependencies: Vec<MapDependency<'_>> = Vec::new();
+        for (kind, file) in [
+            (DependencyKind::ModPack, &self.mod_pack),
+            (DependencyKind::Music, &self.custom_music),
+        ]
+        .into_iter()
+        .chain(block_skins)
+        .chain(item_skins)
+        {
+            let Some(file) = file.as_ref().filter(|file| !file.path.is_empty()) else {
+                continue;
+            };
+            if !dependencies
+                .iter()
+                .any(|known| known.file.path.eq_ignore_ascii_case(&file.path))
+            {
+                dependencies.push(MapDependency {
+                    kind,
+                    file: file.clone(),
+                });
+            }
+        }
+
+        // the XML header also knows where to download some of the files
+        for dep in self
+            .header_xml()
+            .map(|xml| xml.dependencies)
+            .unwrap_or_default()
+        {
+            match dependencies
+                .iter_mut()
+                .find(|known| known.file.path.eq_ignore_ascii_case(&dep.file))
+            {
+                Some(known) if known.file.locator_url.is_none() => {
+                    known.file.locator_url = dep.url;
+                }
+                Some(_) => {}
+                None => dependencies.push(MapDependency {
+                    kind: DependencyKind::Other,
+                    file: FileRef {
+                        path: dep.file,
+                        checksum: None,
+                        locator_url: dep.url,
+                    },
+                }),
+            }
+        }
+
+        dependencies
+    }
+
+    /// The XML header chunk, parsed
+    pub fn header_xml(&self) -> Option<HeaderXml<'_>> {
+        HeaderXml::parse(self.xml_data.as_deref()?)
+    }
+
+    /// Files from the embedded custom item archive, as (path, data) pairs
+    pub fn embedded_files(&self) -> Result<Vec<(String, Vec<u8>)>, GbxError> {
+        self.embedded_files_with_limits(&ParseLimits::default())
+    }
+
+    /// Like [`CtnChallenge::embedded_files`], but the files can't decompress to more than
+    /// `limits.max_decompressed_size` bytes altogether
+    pub fn embedded_files_with_limits(
+        &self,
+        limits: &ParseLimits,
+    ) -> Result<Vec<(String, Vec<u8>)>, GbxError> {
+        let Some(embedded_zip) = self.embedded_zip.as_deref() else {
+            return Ok(Vec::new());
+        };
+        let limit = limits.max_decompressed_size;
+        let size_limit = |size: u64| GbxErrorInner::DecompressedSizeLimit {
+            size: usize::try_from(size).unwrap_or(usize::MAX),
+            limit,
+        };
+
+        let mut archive =
+            zip::ZipArchive::new(Cursor::new(embedded_zip)).context("Opening embedded archive")?;
+
+        let mut files = Vec::new();
+        let mut total = 0u64;
+        for i in 0..archive.len() {
+            let mut file = archive
+                .by_index(i)
+                .with_context(|| format!("Reading embedded file {i}"))?;
+            if file.is_dir() {
+                continue;
+            }
+
+            // the size in the archive can't be trusted either, so reading stops past the limit
+            let remaining = limit as u64 - total;
+            if file.size() > remaining {
+                return Err(size_limit(total.saturating_add(file.size())))
+                    .with_context(|| format!("Decompressing embedded file {}", file.name()));
+            }
+            let mut data = Vec::new();
+            (&mut file)
+                .take(remaining + 1)
+                .read_to_end(&mut data)
+                .with_context(|| format!("Decompressing embedded file {}", file.name()))?;
+            total += data.len() as u64;
+            if total > limit as u64 {
+                return Err(size_limit(total))
+                    .with_context(|| format!("Decompressing embedded file {}", file.name()));
+            }
+            files.push((file.name().to_owned(), data));
+        }
+
+        Ok(files)
+    }
+}
+
+impl ControlEffectSimi<'_> {
+    /// The times of the first and last keyframes
+    pub fn time_range(&self) -> (Option<f32>, Option<f32>) {
+        (
+            self.keys.first().map(|key| key.time),
+            self.keys.last().map(|key| key.time),
+        )
+    }
+}
+
+impl CGame<'_> {
+    /// When a MediaTracker block starts and ends, in seconds, if this is one and it is known
+    pub fn media_time_range(&self) -> Option<(f32, f32)> {
+        let (start, end) = match self {
+            CGame::CtnMediaBlockCameraGame(block) => (block.start, block.end),
+            CGame::CtnMediaBlockText(block) => (block.start, block.end),
+            CGame::CtnMediaBlockImage(block) => (block.start, block.end),
+            CGame::CtnMediaBlockTime(block) => (block.start, block.end),
+            CGame::CtnMediaBlockTransitionFade(block) => (block.start, block.end),
+            CGame::CtnMediaBlockCameraEffectShake(block) => (block.start, block.end),
+            _ => return None,
+        };
+        start.zip(end)
+    }
+}
+
@@ -0,0 +1,344 @@
+//! Chunk handlers registered at runtime, for chunks and classes the built-in parser doesn't
+//! cover. Register handlers on a [`ChunkRegistry`], attach it with [`Node::with_registry`], and
+//! read the results back from the `extensions` field of the parsed nodes.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), gbx_rs::GbxError> {
+//! use gbx_rs::{parse::CGame, registry::ChunkRegistry, Node};
+//!
+//! let mut registry = ChunkRegistry::new();
+//! registry.register_chunk(0x03043056, |reader| {
+//!     let _version = reader.read_u32()?;
+//!     reader.read_u32()
+//! });
+//!
+//! let data = std::fs::read("map.Map.Gbx").unwrap();
+//! let node = Node::read_from(&data)?.with_registry(registry);
+//! if let CGame::CtnChallenge(map) = node.parse()? {
+//!     let value: Option<&u32> = map.extensions.get(0x03043056);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Node::with_registry`]: crate::Node::with_registry
+
+use crate::{
+    cursor::BodyCursor,
+    parse::{wrap_chunk_id, LAST_CHUNK_ID},
+    Context, FileRef, GbxError, GbxErrorInner, IntoOwned, Meta, SkippedChunk,
+};
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    io::Seek,
+    sync::Arc,
+};
+
+type Handler =
+    dyn Fn(&mut ChunkReader<'_, '_>) -> Result<Arc<dyn Any + Send + Sync>, GbxError> + Send + Sync;
+
+/// Chunk handlers to use on top of the built-in parser
+#[derive(Clone, Default)]
+pub struct ChunkRegistry {
+    handlers: HashMap<u32, Arc<Handler>>,
+    classes: HashSet<u32>,
+}
+
+impl Debug for ChunkRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChunkRegistry")
+            .field("chunks", &self.handlers.keys())
+            .field("classes", &self.classes)
+            .finish()
+    }
+}
+
+impl ChunkRegistry {
+    pub fn new() -> Self {
+        ChunkRegistry::default()
+    }
+
+    /// Parses chunk `chunk_id` (including its class ID) with `handler`, storing the result in the
+    /// node's `extensions` under the same ID. This takes the place of a built-in handler for the
+    /// chunk, if there is one. Chunks stored with an older ID of their class are handled by the
+    /// handler for the current ID, as the built-in parser does.
+    ///
+    /// Skippable and header chunks are skipped to their end afterwards, so `handler` only has to
+    /// read what it needs. For other chunks it must read all of the chunk's data.
+    pub fn register_chunk<T, F>(&mut self, chunk_id: u32, handler: F) -> &mut Self
+    where
+        T: Any + Send + Sync,
+        F: Fn(&mut ChunkReader<'_, '_>) -> Result<T, GbxError> + Send + Sync + 'static,
+    {
+        self.handlers.insert(
+            chunk_id,
+            Arc::new(move |reader: &mut ChunkReader<'_, '_>| {
+                handler(reader).map(|value| Arc::new(value) as Arc<dyn Any + Send + Sync>)
+            }),
+        );
+        self
+    }
+
+    /// Keeps the data of skippable or header chunk `chunk_id` as a `Vec<u8>` extension, instead
+    /// of skipping it
+    pub fn register_raw_chunk(&mut self, chunk_id: u32) -> &mut Self {
+        self.register_chunk(chunk_id, |reader| Ok(reader.read_to_chunk_end()?.to_vec()))
+    }
+
+    /// Parses nodes of class `class_id`, which the built-in parser doesn't know, as
+    /// [`ExtensionNode`]s. Their chunks are parsed by the handlers registered for them; skippable
+    /// chunks without a handler are skipped.
+    pub fn register_class(&mut self, class_id: u32) -> &mut Self {
+        self.classes.insert(class_id);
+        self
+    }
+
+    pub(crate) fn handler(&self, chunk_id: u32) -> Option<&Handler> {
+        self.handlers.get(&chunk_id).map(Arc::as_ref)
+    }
+
+    pub(crate) fn has_class(&self, class_id: u32) -> bool {
+        self.classes.contains(&class_id)
+    }
+}
+
+/// Reads the data of one chunk for a handler in a [`ChunkRegistry`]
+pub struct ChunkReader<'a, 'node> {
+    cursor: &'a mut BodyCursor<'node>,
+}
+
+impl<'a, 'node> ChunkReader<'a, 'node> {
+    pub(crate) fn new(cursor: &'a mut BodyCursor<'node>) -> Self {
+        ChunkReader { cursor }
+    }
+
+    /// Whether this chunk is in the header, as some classes reuse chunk IDs there
+    pub fn is_header(&self) -> bool {
+        self.cursor.is_header()
+    }
+
+    /// The position within the whole body (or file, for header chunks)
+    pub fn position(&self) -> u64 {
+        self.cursor.absolute_position()
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, GbxError> {
+        self.cursor.read_u8()
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, GbxError> {
+        self.cursor.read_u16()
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, GbxError> {
+        self.cursor.read_u32()
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, GbxError> {
+        self.cursor.read_i32()
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, GbxError> {
+        self.cursor.read_u64()
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, GbxError> {
+        self.cursor.read_f32()
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, GbxError> {
+        self.cursor.read_bool()
+    }
+
+    pub fn read_vec2(&mut self) -> Result<[f32; 2], GbxError> {
+        self.cursor.read_vec2()
+    }
+
+    pub fn read_vec3(&mut self) -> Result<[f32; 3], GbxError> {
+        self.cursor.read_vec3()
+    }
+
+    pub fn read_int3(&mut self) -> Result<[u32; 3], GbxError> {
+        self.cursor.read_int3()
+    }
+
+    pub fn read_bytes(&mut self, count: usize) -> Result<&'node [u8], GbxError> {
+        self.cursor.read_bytes(count)
+    }
+
+    /// A length-prefixed string
+    pub fn read_string(&mut self) -> Result<&'node str, GbxError> {
+        self.cursor.read_string()
+    }
+
+    /// A string that may refer back to one read earlier in the header or body
+    pub fn read_lookback_string(&mut self) -> Result<&'node str, GbxError> {
+        self.cursor.read_lookback_string()
+    }
+
+    /// Three lookback strings: ID, collection and author
+    pub fn read_meta(&mut self) -> Result<Meta<'node>, GbxError> {
+        self.cursor.read_meta()
+    }
+
+    pub fn read_file_ref(&mut self) -> Result<FileRef<'node>, GbxError> {
+        self.cursor.read_file_ref()
+    }
+
+    /// The rest of a skippable or header chunk's data. Fails for other chunks, as their size isn't
+    /// known.
+    pub fn read_to_chunk_end(&mut self) -> Result<&'node [u8], GbxError> {
+        let Some(chunk_end) = self.cursor.chunk_end() else {
+            return Err(GbxErrorInner::Handler("chunk size is not known".into()).into());
+        };
+        let remaining = chunk_end.saturating_sub(self.cursor.position());
+        self.cursor.read_bytes(remaining as usize)
+    }
+}
+
+/// Results of the [`ChunkRegistry`] handlers that ran on a node, by chunk ID
+#[derive(Clone, Default)]
+pub struct Extensions(HashMap<u32, Arc<dyn Any + Send + Sync>>);
+
+impl Debug for Extensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(self.0.keys().map(|chunk_id| format!("{:08x}", chunk_id)))
+            .finish()
+    }
+}
+
+/// Handler results can't be compared, so only which chunks they are for is
+impl PartialEq for Extensions {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.keys().all(|chunk_id| other.contains(*chunk_id))
+    }
+}
+
+impl Extensions {
+    /// The result of the handler for `chunk_id`, if it ran and returned a `T`
+    pub fn get<T: Any>(&self, chunk_id: u32) -> Option<&T> {
+        self.0.get(&chunk_id)?.downcast_ref()
+    }
+
+    pub fn contains(&self, chunk_id: u32) -> bool {
+        self.0.contains_key(&chunk_id)
+    }
+
+    pub fn chunk_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.0.keys().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn insert(&mut self, chunk_id: u32, value: Arc<dyn Any + Send + Sync>) {
+        self.0.insert(chunk_id, value);
+    }
+}
+
+impl IntoOwned for Extensions {
+    type Owned = Extensions;
+
+    fn into_owned(self) -> Self::Owned {
+        self
+    }
+}
+
+/// A node of a class registered with [`ChunkRegistry::register_class`]
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct ExtensionNode {
+    pub class_id: u32,
+    #[serde(skip)]
+    pub extensions: Extensions,
+}
+
+impl ExtensionNode {
+    pub(crate) fn parse(cursor: &mut BodyCursor<'_>, class_id: u32) -> Result<Self, GbxError> {
+        let mut this = ExtensionNode {
+            class_id,
+            extensions: Extensions::default(),
+        };
+        loop {
+            let full_chunk_id = cursor.read_u32().context("Reading full chunk ID")?;
+            if full_chunk_id == LAST_CHUNK_ID {
+                break;
+            }
+            this.parse_one(cursor, full_chunk_id)?;
+        }
+        Ok(this)
+    }
+
+    pub(crate) fn parse_one(
+        &mut self,
+        cursor: &mut BodyCursor<'_>,
+        full_chunk_id: u32,
+    ) -> Result<(), GbxError> {
+        cursor.in_chunk(full_chunk_id, |cursor| {
+            self.parse_chunk(cursor, full_chunk_id)
+        })
+    }
+
+    fn parse_chunk(
+        &mut self,
+        cursor: &mut BodyCursor<'_>,
+        full_chunk_id: u32,
+    ) -> Result<(), GbxError> {
+        let wrapped_chunk_id = wrap_chunk_id(full_chunk_id);
+        let skippable_size = cursor
+            .read_skippable()
+            .context("Peeking for skippable chunk")?;
+        let registry = cursor.registry();
+        let Some(handler) = registry
+            .as_deref()
+            .and_then(|registry| registry.handler(wrapped_chunk_id))
+        else {
+            let Some(size) = skippable_size else {
+                return Err(GbxErrorInner::InvalidChunkForClass {
+                    chunk_id: full_chunk_id,
+                    class_id: self.class_id,
+                }
+                .into());
+            };
+
+            tracing::warn!(
+                "Unsupported: class {:08x} chunk id {:08x}",
+                self.class_id,
+                full_chunk_id
+            );
+            let offset = cursor.absolute_position();
+            cursor.report().skipped_chunks.push(SkippedChunk {
+                chunk_id: full_chunk_id,
+                class_id: self.class_id,
+                size,
+                offset,
+                header: cursor.is_header(),
+            });
+            return cursor
+                .seek_relative(size as i64)
+                .context("Skipping skippable chunk");
+        };
+
+        let chunk_end = skippable_size.map(|size| cursor.position() + size as u64);
+        // header chunks have their size in the header, so the caller already set their end
+        if chunk_end.is_some() || !cursor.is_header() {
+            cursor.set_chunk_end(chunk_end);
+        }
+        let value = handler(&mut ChunkReader::new(cursor)).with_context(|| {
+            format!(
+                "Handling chunk {:08x} for class {:08x}",
+                wrapped_chunk_id, self.class_id
+            )
+        })?;
+        self.extensions.insert(wrapped_chunk_id, value);
+
+        if let Some(chunk_end) = chunk_end {
+            cursor.set_position(chunk_end);
+        }
+        Ok(())
+    }
+}
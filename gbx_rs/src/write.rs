@@ -0,0 +1,776 @@
+use crate::{
+    collection,
+    cursor::{BodyCursor, Lookback, SKIP},
+    parse::{class_wrap, CtnChallenge, Parsable, RawChunk, RecordedChunks, LAST_CHUNK_ID},
+    ByteFormat, Compression, Context, EditorMode, GbxError, GbxErrorInner, Meta, Node,
+    EDITOR_MODE_GAMEPAD, EDITOR_MODE_GHOST_BLOCKS, EDITOR_MODE_SIMPLE,
+};
+use std::io::{Cursor, Seek};
+
+#[derive(Debug, Default)]
+pub(crate) struct BodyWriter<'a> {
+    inner: Vec<u8>,
+    lookback: Lookback<'a>,
+}
+
+impl<'a> BodyWriter<'a> {
+    pub fn new() -> Self {
+        BodyWriter::default()
+    }
+
+    pub fn with_lookback(lookback: Lookback<'a>) -> Self {
+        BodyWriter {
+            inner: Vec::new(),
+            lookback,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.inner
+    }
+
+    /// Appends data written with `other`, taking over its lookback strings
+    pub fn append(&mut self, other: BodyWriter<'a>) {
+        self.inner.extend_from_slice(&other.inner);
+        self.lookback = other.lookback;
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.inner.extend_from_slice(bytes);
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.inner.push(value);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_u128(&mut self, value: u128) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_vec2(&mut self, value: [f32; 2]) {
+        self.write_f32(value[0]);
+        self.write_f32(value[1]);
+    }
+
+    pub fn write_string(&mut self, str: &str) {
+        self.write_u32(str.len() as u32);
+        self.write_bytes(str.as_bytes());
+    }
+
+    /// Writes `meta`, with its collection stored as in [`BodyWriter::write_collection`]
+    pub fn write_meta(&mut self, meta: &'a Meta<'_>, original_collection_id: Option<u32>) {
+        self.write_lookback_string(&meta.id);
+        self.write_collection(&meta.collection, original_collection_id);
+        self.write_lookback_string(&meta.author);
+    }
+
+    /// Writes a collection name the way the original one was stored: as a lookback string, or as
+    /// a numeric ID if it was `original_id`. That ID is kept as long as the name is still its
+    /// name, since some names have several IDs.
+    pub fn write_collection(&mut self, name: &'a str, original_id: Option<u32>) {
+        let id = original_id.and_then(|id| match collection::name(id).unwrap_or_default() {
+            original_name if original_name == name => Some(id),
+            _ => collection::id(name),
+        });
+        let Some(id) = id else {
+            self.write_lookback_string(name);
+            return;
+        };
+
+        if self.lookback.version.is_none() {
+            self.lookback.version = Some(3);
+            self.write_u32(3);
+        }
+        self.write_u32(id);
+    }
+
+    pub fn write_lookback_string(&mut self, str: &'a str) {
+        if self.lookback.version.is_none() {
+            self.lookback.version = Some(3);
+            self.write_u32(3);
+        }
+
+        if str.is_empty() {
+            self.write_u32(0xffff_ffff);
+            return;
+        }
+
+        if str == "Unassigned" {
+            self.write_u32(0xbfff_ffff);
+            return;
+        }
+
+        match self.lookback.strings.iter().position(|known| *known == str) {
+            Some(index) => self.write_u32(0x4000_0000 | (index as u32 + 1)),
+            None => {
+                self.write_u32(0x4000_0000);
+                self.write_string(str);
+                self.lookback.strings.push(str);
+            }
+        }
+    }
+
+    /// Writes `meta` in place of the original one, copying the original bytes if it is unchanged
+    pub fn replace_meta(
+        &mut self,
+        original: &mut BodyCursor<'a>,
+        meta: Option<&'a Meta<'_>>,
+    ) -> Result<(), GbxError> {
+        let start = original.position() as usize;
+        let lookback_before = original.lookback().clone();
+        let id = original.read_lookback_string()?;
+        let (collection, collection_id) = original.read_lookback()?;
+        let author = original.read_lookback_string()?;
+
+        match meta {
+            Some(meta)
+                if [&*meta.id, &*meta.collection, &*meta.author] != [id, collection, author] =>
+            {
+                self.write_meta(meta, collection_id)
+            }
+            _ if self.lookback == lookback_before => {
+                let end = original.position() as usize;
+                self.write_bytes(&original.get_ref()[start..end]);
+                self.lookback = original.lookback().clone();
+            }
+            _ => {
+                self.write_lookback_string(id);
+                self.write_collection(collection, collection_id);
+                self.write_lookback_string(author);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes `str` in place of the original lookback string, copying the original bytes if it is
+    /// unchanged
+    pub fn replace_lookback_string(
+        &mut self,
+        original: &mut BodyCursor<'a>,
+        str: Option<&'a str>,
+    ) -> Result<(), GbxError> {
+        let start = original.position() as usize;
+        let lookback_before = original.lookback().clone();
+        let original_str = original.read_lookback_string()?;
+
+        match str {
+            Some(str) if str != original_str => self.write_lookback_string(str),
+            _ if self.lookback == lookback_before => {
+                let end = original.position() as usize;
+                self.write_bytes(&original.get_ref()[start..end]);
+                self.lookback = original.lookback().clone();
+            }
+            _ => self.write_lookback_string(original_str),
+        }
+
+        Ok(())
+    }
+
+    pub fn copy_u8(&mut self, original: &mut BodyCursor<'a>) -> Result<u8, GbxError> {
+        let value = original.read_u8()?;
+        self.write_u8(value);
+        Ok(value)
+    }
+
+    pub fn copy_u32(&mut self, original: &mut BodyCursor<'a>) -> Result<u32, GbxError> {
+        let value = original.read_u32()?;
+        self.write_u32(value);
+        Ok(value)
+    }
+
+    pub fn copy_u64(&mut self, original: &mut BodyCursor<'a>) -> Result<u64, GbxError> {
+        let value = original.read_u64()?;
+        self.write_u64(value);
+        Ok(value)
+    }
+
+    /// Copies whatever is left of the original chunk. The copied bytes may refer to lookback
+    /// strings by index, so this refuses edits that changed the strings known at this point.
+    pub fn copy_rest(
+        &mut self,
+        original: &mut BodyCursor<'a>,
+        chunk: &RawChunk<'a>,
+    ) -> Result<(), GbxError> {
+        let position = original.position() as usize;
+        let rest = &original.get_ref()[position..];
+        if rest.is_empty() {
+            return Ok(());
+        }
+
+        if self.lookback != *original.lookback() {
+            return Err(GbxErrorInner::LookbackConflict(chunk.id).into());
+        }
+
+        self.write_bytes(rest);
+        original
+            .seek_relative(rest.len() as i64)
+            .context("Seeking after copying chunk data")?;
+        self.lookback = chunk.lookback_after.clone();
+        Ok(())
+    }
+}
+
+pub(crate) fn write_challenge<'a>(
+    node: &'a Node,
+    challenge: &'a CtnChallenge<'_>,
+) -> Result<Vec<u8>, GbxError> {
+    let header = &node.header;
+    if header.byte_format != ByteFormat::Binary {
+        return Err(GbxErrorInner::WriteText.into());
+    }
+    if header.num_external_nodes > 0 {
+        return Err(GbxErrorInner::WriteExternalNodes.into());
+    }
+
+    let mut recorded = RecordedChunks::default();
+    let original = CtnChallenge::coerce(node.parse_recorded(Some(&mut recorded))?.0)?;
+
+    let mut header_data = BodyWriter::new();
+    let mut header_chunks = Vec::new();
+    for chunk in recorded.header.iter() {
+        let start = header_data.len();
+        let mut original = BodyCursor::new(Cursor::new(chunk.data));
+        original.set_lookback(chunk.lookback_before.clone());
+
+        match chunk.id & 0xfff {
+            0x002 => write_map_info_1(&mut header_data, &mut original, challenge),
+            0x003 => write_map_info_2(&mut header_data, &mut original, challenge),
+            0x004 => write_header_version(&mut header_data, &mut original, challenge),
+            0x005 => write_xml_data(&mut header_data, &mut original, challenge),
+            0x007 => write_thumbnail(&mut header_data, &mut original, challenge),
+            0x008 => write_author(&mut header_data, &mut original, challenge),
+            _ => Ok(()),
+        }
+        .and_then(|_| header_data.copy_rest(&mut original, chunk))
+        .with_context(|| format!("Writing header chunk {:08x}", chunk.id))?;
+
+        header_chunks.push((chunk.id, chunk.heavy, header_data.len() - start));
+    }
+
+    // the body keeps a password hash next to the password in the header
+    let password_cleared = challenge.password.as_deref().is_some_and(str::is_empty)
+        && original
+            .password
+            .as_ref()
+            .is_some_and(|password| !password.is_empty());
+
+    let mut body = BodyWriter::new();
+    for chunk in recorded.body.iter() {
+        let chunk_id = class_wrap(chunk.id & 0xffff_f000) | (chunk.id & 0xfff);
+        if chunk_id == 0x03043029 && password_cleared {
+            continue;
+        }
+
+        let mut original = BodyCursor::new(Cursor::new(chunk.payload()));
+        original.set_lookback(chunk.lookback_before.clone());
+        let mut data = BodyWriter::with_lookback(body.lookback.clone());
+
+        match chunk_id {
+            0x0304301f => write_block_data(&mut data, &mut original, challenge),
+            _ => Ok(()),
+        }
+        .and_then(|_| data.copy_rest(&mut original, chunk))
+        .with_context(|| format!("Writing body chunk {:08x}", chunk.id))?;
+
+        body.write_u32(chunk.id);
+        if chunk.is_skippable() {
+            body.write_u32(SKIP);
+            body.write_u32(data.len() as u32);
+        }
+        body.append(data);
+    }
+    body.write_u32(LAST_CHUNK_ID);
+    let body = body.into_inner();
+
+    let mut file = BodyWriter::new();
+    file.write_bytes(b"GBX");
+    file.write_u16(header.version);
+    file.write_u8(header.byte_format.into());
+    file.write_u8(header.ref_table_compression.into());
+    file.write_u8(header.body_compression.into());
+    if let Some(unknown_flag) = header.unknown_flag {
+        file.write_u8(unknown_flag);
+    }
+    file.write_u32(header.class_id);
+
+    if header.version >= 6 {
+        let user_data_size = 4 + 8 * header_chunks.len() + header_data.len();
+        file.write_u32(user_data_size as u32);
+    }
+
+    file.write_u32(header_chunks.len() as u32);
+    for (id, heavy, size) in header_chunks {
+        file.write_u32(id);
+        file.write_u32(size as u32 | if heavy { 0x8000_0000 } else { 0 });
+    }
+    file.write_bytes(&header_data.into_inner());
+
+    file.write_u32(header.num_nodes);
+    file.write_u32(header.num_external_nodes);
+
+    match header.body_compression {
+        Compression::Compressed => {
+            // keep the original compressed data when nothing changed so files round-trip exactly
+            let compressed = match node.raw_body() {
+                Some(raw_body) if body == node.body()? => raw_body.to_vec(),
+                _ => lzokay_native::compress(&body).context("Compressing body")?,
+            };
+
+            file.write_u32(body.len() as u32);
+            file.write_u32(compressed.len() as u32);
+            file.write_bytes(&compressed);
+        }
+
+        Compression::Uncompressed => file.write_bytes(&body),
+    }
+
+    let file = file.into_inner();
+    let mut written_node = Node::read_from(&file).context("Reading written file")?;
+    written_node.registry = node.registry.clone();
+    written_node.limits = node.limits;
+    let written = CtnChallenge::coerce(written_node.parse().context("Parsing written file")?)?;
+    check_fields(challenge, &original, &written)?;
+    drop(written);
+
+    Ok(file)
+}
+
+/// Fails if a field of `challenge` was lost when writing it: one the writer copies from the
+/// `original` file but that was changed, or one it writes but that is different in the `written`
+/// file, because the original file doesn't store it or can't store that value
+fn check_fields(
+    challenge: &CtnChallenge<'_>,
+    original: &CtnChallenge<'_>,
+    written: &CtnChallenge<'_>,
+) -> Result<(), GbxError> {
+    // no `..`, so a new field has to be sorted into one of the lists below
+    let CtnChallenge {
+        phantom: _,
+        map_name,
+        header_map_kind,
+        map_info,
+        decoration,
+        size,
+        bronze_time,
+        silver_time,
+        gold_time,
+        author_time,
+        cost,
+        is_lap_race,
+        num_laps,
+        num_checkpoints,
+        play_mode,
+        editor_mode,
+        has_ghost_blocks,
+        gamepad_editor,
+        password,
+        map_coord_origin,
+        map_coord_target,
+        pack_mask,
+        map_type,
+        map_style,
+        lightmap_version,
+        title_id,
+        header_version,
+        xml_data,
+        thumbnail_data,
+        author_login,
+        author_nickname,
+        author_zone,
+        author_extra_info,
+        vehicle_model,
+        block_stock,
+        challenge_parameters,
+        map_kind,
+        blocks,
+        items,
+        embedded_item_models,
+        embedded_zip,
+        embedded_textures,
+        mod_pack,
+        custom_music,
+        clip_intro,
+        clip_podium,
+        clip_group_in_game,
+        clip_group_end_race,
+        clip_ambiance,
+        clip_trigger_size,
+        extensions,
+    } = challenge;
+
+    // the first of `fields` for which `test` holds, given its value and its value in `other`
+    macro_rules! find_field {
+        ($other:ident, |$value:ident, $other_value:ident| $test:expr; $($field:ident),* $(,)?) => {
+            [$({
+                let ($value, $other_value) = ($field, &$other.$field);
+                (stringify!($field), $test)
+            }),*]
+            .into_iter()
+            .find_map(|(field, found)| found.then_some(field))
+        };
+    }
+
+    let changed = find_field!(
+        original, |value, original| value != original;
+        vehicle_model, block_stock, challenge_parameters, map_kind, blocks, items,
+        embedded_item_models, embedded_zip, embedded_textures, mod_pack, custom_music,
+        clip_intro, clip_podium, clip_group_in_game, clip_group_end_race, clip_ambiance,
+        clip_trigger_size, extensions,
+    );
+    if let Some(field) = changed {
+        return Err(GbxErrorInner::WriteUnsupportedField(field).into());
+    }
+
+    // fields left as `None` keep their original value
+    let unwritten = find_field!(
+        written, |value, written| value.is_some() && value != written;
+        map_name, header_map_kind, map_info, decoration, size, bronze_time, silver_time,
+        gold_time, author_time, cost, is_lap_race, num_laps, num_checkpoints, play_mode,
+        editor_mode, has_ghost_blocks, gamepad_editor, password, map_coord_origin,
+        map_coord_target, pack_mask, map_type, map_style, lightmap_version, title_id,
+        header_version, xml_data, thumbnail_data, author_login, author_nickname, author_zone,
+        author_extra_info,
+    );
+    if let Some(field) = unwritten {
+        return Err(GbxErrorInner::WriteUnstoredField(field).into());
+    }
+
+    Ok(())
+}
+
+fn write_map_info_1<'a>(
+    writer: &mut BodyWriter<'a>,
+    original: &mut BodyCursor<'a>,
+    challenge: &'a CtnChallenge<'_>,
+) -> Result<(), GbxError> {
+    let version = writer
+        .copy_u8(original)
+        .context("Writing map info 1 version")?;
+
+    if version <= 2 {
+        writer
+            .replace_meta(original, challenge.map_info.as_ref())
+            .context("Writing map info 1 map info")?;
+        let map_name = original
+            .read_string()
+            .context("Reading map info 1 map name")?;
+        writer.write_string(challenge.map_name.as_deref().unwrap_or(map_name));
+    }
+
+    writer
+        .copy_u32(original)
+        .context("Writing map info 1 unknown 1")?;
+
+    if version >= 1 {
+        for time in [
+            challenge.bronze_time,
+            challenge.silver_time,
+            challenge.gold_time,
+            challenge.author_time,
+        ] {
+            let original_time = original
+                .read_i32()
+                .context("Reading map info 1 medal time")?;
+            writer.write_i32(time.unwrap_or(original_time));
+        }
+    }
+
+    if version == 2 {
+        writer
+            .copy_u8(original)
+            .context("Writing map info 1 unknown 2")?;
+    }
+
+    if version >= 4 {
+        let cost = original.read_u32().context("Reading map info 1 cost")?;
+        writer.write_u32(challenge.cost.unwrap_or(cost));
+    }
+
+    if version >= 5 {
+        let is_lap_race = original.read_u32().context("Reading lap race")?;
+        writer.write_u32(challenge.is_lap_race.map_or(is_lap_race, u32::from));
+    }
+
+    if version == 6 {
+        writer.copy_u32(original).context("Writing is multilap")?;
+    }
+
+    if version >= 7 {
+        let play_mode = original.read_u32().context("Reading play mode")?;
+        writer.write_u32(challenge.play_mode.map_or(play_mode, |mode| mode as u32));
+    }
+
+    if version >= 9 {
+        writer
+            .copy_u32(original)
+            .context("Writing map info 1 unknown 3")?;
+    }
+
+    if version >= 10 {
+        writer
+            .copy_u32(original)
+            .context("Writing map info 1 author score")?;
+    }
+
+    if version >= 11 {
+        let mut editor_mode = original.read_u32().context("Reading editor mode")?;
+        for (flag, value) in [
+            (
+                EDITOR_MODE_SIMPLE,
+                challenge.editor_mode.map(|mode| mode == EditorMode::Simple),
+            ),
+            (EDITOR_MODE_GHOST_BLOCKS, challenge.has_ghost_blocks),
+            (EDITOR_MODE_GAMEPAD, challenge.gamepad_editor),
+        ] {
+            match value {
+                Some(true) => editor_mode |= flag,
+                Some(false) => editor_mode &= !flag,
+                None => {}
+            }
+        }
+        writer.write_u32(editor_mode);
+    }
+
+    if version >= 12 {
+        writer
+            .copy_u32(original)
+            .context("Writing map info 1 unknown 4")?;
+    }
+
+    if version >= 13 {
+        let num_checkpoints = original.read_u32().context("Reading num checkpoints")?;
+        writer.write_u32(challenge.num_checkpoints.unwrap_or(num_checkpoints));
+        let num_laps = original.read_u32().context("Reading num laps")?;
+        writer.write_u32(challenge.num_laps.unwrap_or(num_laps));
+    }
+
+    Ok(())
+}
+
+fn write_map_info_2<'a>(
+    writer: &mut BodyWriter<'a>,
+    original: &mut BodyCursor<'a>,
+    challenge: &'a CtnChallenge<'_>,
+) -> Result<(), GbxError> {
+    let version = writer
+        .copy_u8(original)
+        .context("Writing map info 2 version")?;
+
+    writer
+        .replace_meta(original, challenge.map_info.as_ref())
+        .context("Writing map info 2 map info")?;
+    let map_name = original
+        .read_string()
+        .context("Reading map info 2 map name")?;
+    writer.write_string(challenge.map_name.as_deref().unwrap_or(map_name));
+    let map_kind = original.read_u8().context("Reading map info 2 map kind")?;
+    writer.write_u8(
+        challenge
+            .header_map_kind
+            .clone()
+            .map_or(map_kind, |kind| kind as u8),
+    );
+
+    if version >= 1 {
+        writer
+            .copy_u32(original)
+            .context("Writing map info 2 unknown 1")?;
+        let password = original
+            .read_string()
+            .context("Reading map info 2 password")?;
+        writer.write_string(challenge.password.as_deref().unwrap_or(password));
+    }
+
+    if version >= 2 {
+        writer
+            .replace_meta(original, challenge.decoration.as_ref())
+            .context("Writing map info 2 decoration")?;
+    }
+
+    if version >= 3 {
+        let origin = original
+            .read_vec2()
+            .context("Reading map info 2 map coord origin")?;
+        writer.write_vec2(challenge.map_coord_origin.unwrap_or(origin));
+    }
+
+    if version >= 4 {
+        let target = original
+            .read_vec2()
+            .context("Reading map info 2 map coord target")?;
+        writer.write_vec2(challenge.map_coord_target.unwrap_or(target));
+    }
+
+    if version >= 5 {
+        let pack_mask = original
+            .read_u128()
+            .context("Reading map info 2 pack mask")?;
+        writer.write_u128(challenge.pack_mask.unwrap_or(pack_mask));
+    }
+
+    if version >= 6 {
+        let map_type = original
+            .read_string()
+            .context("Reading map info 2 map type")?;
+        writer.write_string(challenge.map_type.as_deref().unwrap_or(map_type));
+        let map_style = original
+            .read_string()
+            .context("Reading map info 2 map style")?;
+        writer.write_string(challenge.map_style.as_deref().unwrap_or(map_style));
+    }
+
+    if version >= 8 {
+        writer
+            .copy_u64(original)
+            .context("Writing map info 2 lightmap cache uid")?;
+    }
+
+    if version >= 9 {
+        let lightmap_version = original
+            .read_u8()
+            .context("Reading map info 2 lightmap version")?;
+        writer.write_u8(challenge.lightmap_version.unwrap_or(lightmap_version));
+    }
+
+    if version >= 11 {
+        writer
+            .replace_lookback_string(original, challenge.title_id.as_deref())
+            .context("Writing map info 2 title ID")?;
+    }
+
+    Ok(())
+}
+
+fn write_header_version<'a>(
+    writer: &mut BodyWriter<'a>,
+    original: &mut BodyCursor<'a>,
+    challenge: &'a CtnChallenge<'_>,
+) -> Result<(), GbxError> {
+    let header_version = original.read_u32().context("Reading header version")?;
+    writer.write_u32(challenge.header_version.unwrap_or(header_version));
+    Ok(())
+}
+
+fn write_xml_data<'a>(
+    writer: &mut BodyWriter<'a>,
+    original: &mut BodyCursor<'a>,
+    challenge: &'a CtnChallenge<'_>,
+) -> Result<(), GbxError> {
+    let xml_data = original.read_string().context("Reading XML data")?;
+    writer.write_string(challenge.xml_data.as_deref().unwrap_or(xml_data));
+    Ok(())
+}
+
+fn write_thumbnail<'a>(
+    writer: &mut BodyWriter<'a>,
+    original: &mut BodyCursor<'a>,
+    challenge: &'a CtnChallenge<'_>,
+) -> Result<(), GbxError> {
+    writer
+        .copy_u32(original)
+        .context("Writing thumbnail version")?;
+    let thumbnail_size = original.read_u32().context("Reading thumbnail size")?;
+    let start_tag = original
+        .read_string_exact("<Thumbnail.jpg>".len())
+        .context("Reading thumbnail start tag")?;
+    let thumbnail_data = original
+        .read_bytes(thumbnail_size as usize)
+        .context("Reading thumbnail data")?;
+
+    let thumbnail_data = challenge
+        .thumbnail_data
+        .as_deref()
+        .unwrap_or(thumbnail_data);
+    writer.write_u32(thumbnail_data.len() as u32);
+    writer.write_bytes(start_tag.as_bytes());
+    writer.write_bytes(thumbnail_data);
+
+    for (tag, context) in [
+        ("</Thumbnail.jpg>", "Reading thumbnail end tag"),
+        ("<Comments>", "Reading comments start tag"),
+    ] {
+        let tag = original.read_string_exact(tag.len()).context(context)?;
+        writer.write_bytes(tag.as_bytes());
+    }
+    let comments = original.read_string().context("Reading comments")?;
+    writer.write_string(comments);
+    let end_tag = original
+        .read_string_exact("</Comments>".len())
+        .context("Reading comments end tag")?;
+    writer.write_bytes(end_tag.as_bytes());
+    Ok(())
+}
+
+fn write_author<'a>(
+    writer: &mut BodyWriter<'a>,
+    original: &mut BodyCursor<'a>,
+    challenge: &'a CtnChallenge<'_>,
+) -> Result<(), GbxError> {
+    writer
+        .copy_u32(original)
+        .context("Writing author information version")?;
+    writer
+        .copy_u32(original)
+        .context("Writing author version")?;
+
+    for field in [
+        &challenge.author_login,
+        &challenge.author_nickname,
+        &challenge.author_zone,
+        &challenge.author_extra_info,
+    ] {
+        let value = original
+            .read_string()
+            .context("Reading author information")?;
+        writer.write_string(field.as_deref().unwrap_or(value));
+    }
+
+    Ok(())
+}
+
+fn write_block_data<'a>(
+    writer: &mut BodyWriter<'a>,
+    original: &mut BodyCursor<'a>,
+    challenge: &'a CtnChallenge<'_>,
+) -> Result<(), GbxError> {
+    writer
+        .replace_meta(original, challenge.map_info.as_ref())
+        .context("Writing map info")?;
+    let map_name = original.read_string().context("Reading map name")?;
+    writer.write_string(challenge.map_name.as_deref().unwrap_or(map_name));
+    writer
+        .replace_meta(original, challenge.decoration.as_ref())
+        .context("Writing decoration")?;
+
+    let size = original.read_int3().context("Reading map size")?;
+    for value in challenge.size.unwrap_or(size) {
+        writer.write_u32(value);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,312 @@
+//! The XML summary that maps and replays keep in their header
+
+use crate::{parse::CtnChallenge, IntoOwned};
+use std::{borrow::Cow, fmt::Display};
+
+/// The contents of a map's XML header chunk (0x03043005)
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct HeaderXml<'node> {
+    pub exe_version: Option<Cow<'node, str>>,
+    pub exe_build: Option<Cow<'node, str>>,
+    pub title_id: Option<Cow<'node, str>>,
+    pub lightmap_version: Option<u8>,
+    pub uid: Option<Cow<'node, str>>,
+    pub name: Option<Cow<'node, str>>,
+    pub author_login: Option<Cow<'node, str>>,
+    pub author_zone: Option<Cow<'node, str>>,
+    pub environment: Option<Cow<'node, str>>,
+    pub mood: Option<Cow<'node, str>>,
+    /// Race, Platform, Puzzle, etc
+    pub kind: Option<Cow<'node, str>>,
+    pub map_type: Option<Cow<'node, str>>,
+    pub map_style: Option<Cow<'node, str>>,
+    pub validated: Option<bool>,
+    pub num_laps: Option<u32>,
+    pub display_cost: Option<u32>,
+    pub mod_name: Option<Cow<'node, str>>,
+    pub has_ghost_blocks: Option<bool>,
+    pub player_model: Option<Cow<'node, str>>,
+    pub bronze_time: Option<i32>,
+    pub silver_time: Option<i32>,
+    pub gold_time: Option<i32>,
+    pub author_time: Option<i32>,
+    pub author_score: Option<i32>,
+    pub dependencies: Vec<Dependency<'node>>,
+}
+
+/// A file the map needs, like a skin or a custom block
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Dependency<'node> {
+    pub file: Cow<'node, str>,
+    pub url: Option<Cow<'node, str>>,
+}
+
+/// A value that differs between the XML header and the binary chunks
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Mismatch {
+    pub field: &'static str,
+    pub xml: String,
+    pub binary: String,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: XML header has {:?}, map has {:?}",
+            self.field, self.xml, self.binary
+        )
+    }
+}
+
+impl<'node> HeaderXml<'node> {
+    /// Parses the XML header, skipping any attribute that is missing or malformed
+    pub fn parse(xml: &'node str) -> Option<HeaderXml<'node>> {
+        let header = element(xml, "header")?;
+        let ident = element(xml, "ident").unwrap_or_default();
+        let desc = element(xml, "desc").unwrap_or_default();
+        let times = element(xml, "times").unwrap_or_default();
+        let time = |name| number(times, name).filter(|time: &i32| *time >= 0);
+
+        Some(HeaderXml {
+            exe_version: attribute(header, "exever"),
+            exe_build: attribute(header, "exebuild"),
+            title_id: attribute(header, "title"),
+            lightmap_version: number(header, "lightmap"),
+            uid: attribute(ident, "uid"),
+            name: attribute(ident, "name"),
+            author_login: attribute(ident, "author"),
+            author_zone: attribute(ident, "authorzone"),
+            environment: attribute(desc, "envir"),
+            mood: attribute(desc, "mood"),
+            kind: attribute(desc, "type"),
+            map_type: attribute(desc, "maptype"),
+            map_style: attribute(desc, "mapstyle"),
+            validated: number(desc, "validated").map(|validated: u8| validated != 0),
+            num_laps: number(desc, "nblaps"),
+            display_cost: number(desc, "displaycost"),
+            mod_name: attribute(desc, "mod"),
+            has_ghost_blocks: number(desc, "hasghostblocks").map(|ghost: u8| ghost != 0),
+            player_model: element(xml, "playermodel").and_then(|model| attribute(model, "id")),
+            bronze_time: time("bronze"),
+            silver_time: time("silver"),
+            gold_time: time("gold"),
+            author_time: time("authortime"),
+            author_score: number(times, "authorscore"),
+            dependencies: elements(xml, "dep")
+                .filter_map(|dep| {
+                    Some(Dependency {
+                        file: attribute(dep, "file")?,
+                        url: attribute(dep, "url"),
+                    })
+                })
+                .collect(),
+        })
+    }
+
+    pub fn into_owned(self) -> HeaderXml<'static> {
+        HeaderXml {
+            exe_version: IntoOwned::into_owned(self.exe_version),
+            exe_build: IntoOwned::into_owned(self.exe_build),
+            title_id: IntoOwned::into_owned(self.title_id),
+            uid: IntoOwned::into_owned(self.uid),
+            name: IntoOwned::into_owned(self.name),
+            author_login: IntoOwned::into_owned(self.author_login),
+            author_zone: IntoOwned::into_owned(self.author_zone),
+            environment: IntoOwned::into_owned(self.environment),
+            mood: IntoOwned::into_owned(self.mood),
+            kind: IntoOwned::into_owned(self.kind),
+            map_type: IntoOwned::into_owned(self.map_type),
+            map_style: IntoOwned::into_owned(self.map_style),
+            mod_name: IntoOwned::into_owned(self.mod_name),
+            player_model: IntoOwned::into_owned(self.player_model),
+            dependencies: IntoOwned::into_owned(self.dependencies),
+            ..self
+        }
+    }
+
+    /// Compares the XML header with what the binary chunks of `challenge` say. Values missing
+    /// from either side are not compared.
+    pub fn mismatches(&self, challenge: &CtnChallenge<'_>) -> Vec<Mismatch> {
+        let map_info = challenge.map_info.as_ref();
+        let mut mismatches = Vec::new();
+
+        for (field, xml, binary) in [
+            ("uid", &self.uid, map_info.map(|meta| &*meta.id)),
+            ("name", &self.name, challenge.map_name.as_deref()),
+            (
+                "author login",
+                &self.author_login,
+                map_info.map(|meta| &*meta.author),
+            ),
+            (
+                "author zone",
+                &self.author_zone,
+                challenge.author_zone.as_deref(),
+            ),
+            ("map type", &self.map_type, challenge.map_type.as_deref()),
+            ("map style", &self.map_style, challenge.map_style.as_deref()),
+            ("title ID", &self.title_id, challenge.title_id.as_deref()),
+        ] {
+            compare(&mut mismatches, field, xml.as_deref(), binary);
+        }
+
+        for (field, xml, binary) in [
+            ("bronze time", self.bronze_time, challenge.bronze_time),
+            ("silver time", self.silver_time, challenge.silver_time),
+            ("gold time", self.gold_time, challenge.gold_time),
+            ("author time", self.author_time, challenge.author_time),
+        ] {
+            compare(&mut mismatches, field, xml, binary);
+        }
+
+        compare(
+            &mut mismatches,
+            "has ghost blocks",
+            self.has_ghost_blocks,
+            challenge.has_ghost_blocks,
+        );
+
+        mismatches
+    }
+}
+
+fn compare<T: PartialEq + Display>(
+    mismatches: &mut Vec<Mismatch>,
+    field: &'static str,
+    xml: Option<T>,
+    binary: Option<T>,
+) {
+    if let (Some(xml), Some(binary)) = (xml, binary) {
+        if xml != binary {
+            mismatches.push(Mismatch {
+                field,
+                xml: xml.to_string(),
+                binary: binary.to_string(),
+            });
+        }
+    }
+}
+
+impl IntoOwned for Dependency<'_> {
+    type Owned = Dependency<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Dependency {
+            file: IntoOwned::into_owned(self.file),
+            url: IntoOwned::into_owned(self.url),
+        }
+    }
+}
+
+/// The attribute text of the first `<name ...>` tag in `xml`
+pub(crate) fn element<'node>(xml: &'node str, name: &str) -> Option<&'node str> {
+    elements(xml, name).next()
+}
+
+/// The attribute text of every `<name ...>` tag in `xml`
+fn elements<'node, 'name>(
+    xml: &'node str,
+    name: &'name str,
+) -> impl Iterator<Item = &'node str> + 'name
+where
+    'node: 'name,
+{
+    let mut rest = xml;
+    std::iter::from_fn(move || loop {
+        let start = rest.find('<')? + 1;
+        rest = &rest[start..];
+        let Some(after_name) = rest.strip_prefix(name) else {
+            continue;
+        };
+        if !after_name.starts_with(|c: char| c.is_whitespace() || c == '/' || c == '>') {
+            continue;
+        }
+
+        // quoted attribute values may contain '>'
+        let mut quote = None;
+        let end = after_name.find(|c: char| match (quote, c) {
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                false
+            }
+            (Some(open), _) if open == c => {
+                quote = None;
+                false
+            }
+            (None, '>') => true,
+            _ => false,
+        })?;
+        rest = &after_name[end..];
+        return Some(after_name[..end].trim_end_matches('/'));
+    })
+}
+
+/// The unescaped value of the attribute `name` in the attribute text of a tag
+pub(crate) fn attribute<'node>(tag: &'node str, name: &str) -> Option<Cow<'node, str>> {
+    let mut rest = tag;
+    loop {
+        rest = rest.trim_start();
+        let (key, after_key) = rest.split_once('=')?;
+        let after_key = after_key.trim_start();
+        let quote = after_key
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')?;
+        let (value, after_value) = after_key[1..].split_once(quote)?;
+        if key.trim_end() == name {
+            return Some(unescape(value));
+        }
+        rest = after_value;
+    }
+}
+
+fn number<T: std::str::FromStr>(tag: &str, name: &str) -> Option<T> {
+    attribute(tag, name)?.parse().ok()
+}
+
+fn unescape(value: &str) -> Cow<'_, str> {
+    if !value.contains('&') {
+        return Cow::Borrowed(value);
+    }
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let char = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+
+        match char {
+            Some(char) => {
+                unescaped.push(char);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+
+    Cow::Owned(unescaped)
+}
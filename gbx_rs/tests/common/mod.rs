@@ -0,0 +1,116 @@
+#![allow(dead_code)]
+
+use gbx_rs::{
+    parse::{CGame, CtnChallenge},
+    Node,
+};
+use std::path::PathBuf;
+
+pub const ARCTIC_LUNGUS: &str = "Lungus 3 - Arctic Lungus.Map.Gbx";
+pub const QUEST_FOR_THE_THIRD_LUNGUS: &str = "Quest for the Third Lungus.Map.Gbx";
+pub const EXAMPLE_MAPS: [&str; 2] = [ARCTIC_LUNGUS, QUEST_FOR_THE_THIRD_LUNGUS];
+
+/// A map bundled in `examples/`
+pub fn example_map(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join(name);
+    std::fs::read(&path).unwrap_or_else(|err| panic!("{}: {}", path.display(), err))
+}
+
+/// A file in `tests/data/`
+pub fn test_data(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("data")
+        .join(name);
+    std::fs::read(&path).unwrap_or_else(|err| panic!("{}: {}", path.display(), err))
+}
+
+/// The map `node` holds
+pub fn parse_map<'node>(node: &'node Node) -> CtnChallenge<'node> {
+    match node.parse().unwrap() {
+        CGame::CtnChallenge(map) => map,
+        _ => panic!("not a map"),
+    }
+}
+
+/// The header of `data` changed to store the body uncompressed, and the decompressed body
+pub fn uncompressed(data: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let node = Node::read_from(&data).unwrap();
+    let body = node.body().unwrap().to_vec();
+    // the compressed body runs to the end of the file, after its two sizes
+    let compressed_size = (1..data.len() - 4)
+        .find(|&size| {
+            let at = data.len() - size - 4;
+            u32::from_le_bytes(data[at..at + 4].try_into().unwrap()) as usize == size
+        })
+        .unwrap();
+
+    let mut header = data[..data.len() - compressed_size - 8].to_vec();
+    header[7] = b'U';
+    (header, body)
+}
+
+/// A binary GBX file of class `class_id` with a single header chunk, that the parser skips, and
+/// `body` stored as is
+pub fn gbx_file(class_id: u32, compressed: bool, uncompressed_size: u32, body: &[u8]) -> Vec<u8> {
+    gbx_file_with_refs(class_id, compressed, uncompressed_size, 0, &[], body)
+}
+
+/// Like [`gbx_file`], with `num_external_nodes` external nodes described by `ref_table`, which
+/// starts at the ancestor level
+pub fn gbx_file_with_refs(
+    class_id: u32,
+    compressed: bool,
+    uncompressed_size: u32,
+    num_external_nodes: u32,
+    ref_table: &[u8],
+    body: &[u8],
+) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(b"GBX");
+    data.extend_from_slice(&6u16.to_le_bytes());
+    data.extend_from_slice(if compressed { b"BUCR" } else { b"BUUR" });
+    data.extend_from_slice(&class_id.to_le_bytes());
+    // user data: one empty header chunk
+    data.extend_from_slice(&12u32.to_le_bytes());
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&(class_id | 0xfff).to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    // nodes, external nodes
+    data.extend_from_slice(&(num_external_nodes + 1).to_le_bytes());
+    data.extend_from_slice(&num_external_nodes.to_le_bytes());
+    if num_external_nodes > 0 {
+        data.extend_from_slice(ref_table);
+    }
+    if compressed {
+        data.extend_from_slice(&uncompressed_size.to_le_bytes());
+        data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    }
+    data.extend_from_slice(body);
+    data
+}
+
+/// A string as stored in binary GBX files
+pub fn gbx_string(value: &str) -> Vec<u8> {
+    let mut data = (value.len() as u32).to_le_bytes().to_vec();
+    data.extend_from_slice(value.as_bytes());
+    data
+}
+
+/// A tiny xorshift generator, so mutations are the same on every run
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}
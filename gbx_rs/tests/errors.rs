@@ -0,0 +1,33 @@
+//! Errors say where in the file parsing failed
+
+mod common;
+
+use common::{example_map, uncompressed, EXAMPLE_MAPS};
+use gbx_rs::Node;
+
+#[test]
+fn truncated_body_location() {
+    for name in EXAMPLE_MAPS {
+        let (header, body) = uncompressed(&example_map(name));
+        let len = body.len() / 2;
+        let truncated = [&header[..], &body[..len]].concat();
+        let node = Node::read_from(&truncated).unwrap();
+        let err = node.parse().unwrap_err();
+
+        // the location is what the server reports for maps it can't parse
+        let location = err.location().expect("location of a body error");
+        assert!(!location.header, "{}", name);
+        assert!(location.offset <= len as u64, "{}: {}", name, location);
+        assert_eq!(err.chunk_stack().len(), 1, "{}: {}", name, location);
+        assert_eq!(err.chunk_stack()[0] & 0xffff_f000, 0x03043000, "{}", name);
+        assert!(location.to_string().contains("of the body"), "{}", location);
+    }
+}
+
+#[test]
+fn header_errors_have_no_chunk_stack() {
+    let data = example_map(EXAMPLE_MAPS[0]);
+    let err = Node::read_from(&&data[..40]).unwrap_err();
+    assert_eq!(err.is_header(), Some(true));
+    assert!(err.chunk_stack().is_empty());
+}
@@ -0,0 +1,84 @@
+//! Parsing ghosts in the binary format
+
+mod common;
+
+use common::test_data;
+use gbx_rs::{parse::CGame, Node};
+
+/// A ghost with every chunk the parser knows, from the sample stream to the validation info
+const BINARY_GHOST: &str = "Binary.Ghost.Gbx";
+
+#[test]
+fn binary_ghost() {
+    let data = test_data(BINARY_GHOST);
+    let node = Node::read_from(&data).unwrap();
+    let (parsed, report) = node.parse_with_report().unwrap();
+    let CGame::CtnGhost(ghost) = parsed else {
+        panic!("not a ghost");
+    };
+    assert!(report.missing_fields.is_empty(), "{:?}", report);
+
+    assert_eq!(ghost.is_replaying, Some(true));
+    assert_eq!(ghost.sample_data_size, Some(8));
+    assert_eq!(ghost.sample_data.as_deref(), Some(&b"abcd"[..]));
+    assert_eq!(ghost.race_time, Some(52340));
+    assert_eq!(ghost.respawns, Some(1));
+    assert_eq!(ghost.light_trail_color, Some([0.0, 1.0, 0.0]));
+    assert_eq!(ghost.stunt_score, Some(7));
+    let times: Vec<_> = ghost.checkpoints.iter().map(|cp| cp.time).collect();
+    assert_eq!(times, [Some(20000), None]);
+    assert_eq!(ghost.uid.as_deref(), Some("GhostUid"));
+    assert_eq!(ghost.login.as_deref(), Some("driver"));
+    assert_eq!(ghost.player_mobil_id.as_deref(), Some("CarSport"));
+
+    let [skin] = &ghost.skin_pack_descs[..] else {
+        panic!("expected one skin");
+    };
+    assert_eq!(skin.path, "Skins\\Models\\CarSport\\Skin.zip");
+    assert_eq!(skin.checksum, None);
+    assert_eq!(
+        skin.locator_url.as_deref(),
+        Some("https://example.com/Skin.zip")
+    );
+    assert_eq!(ghost.nickname.as_deref(), Some("Driver"));
+    assert_eq!(ghost.avatar_name.as_deref(), Some("Avatar"));
+
+    // the mobil ID's lookback string is reused for the player model
+    let player_model = ghost.player_model.unwrap();
+    assert_eq!(player_model.id, "CarSport");
+    assert_eq!(player_model.collection, "Vehicles");
+    assert_eq!(player_model.author, "Nadeo");
+
+    assert_eq!(ghost.events_duration, Some(1000));
+    let inputs: Vec<_> = ghost
+        .inputs
+        .iter()
+        .map(|input| (input.time, &*input.name, input.data))
+        .collect();
+    assert_eq!(inputs, [(0, "Accel", 1)]);
+    assert_eq!(ghost.game_version.as_deref(), Some("3.3.0"));
+}
+
+#[test]
+fn ghost_without_events() {
+    // events duration 0 ends chunk 019 early
+    let mut data = test_data(BINARY_GHOST);
+    let chunk = 0x03092019u32.to_le_bytes();
+    let at = data.windows(4).position(|window| window == chunk).unwrap() + 4;
+    let events = [1000u32.to_le_bytes(), 0u32.to_le_bytes()].concat();
+    assert_eq!(data[at..at + 8], events);
+    // the rest of the chunk, without the duration, up to chunk 01c
+    let end = data
+        .windows(4)
+        .position(|window| window == 0x0309201cu32.to_le_bytes())
+        .unwrap();
+    data.splice(at..end, 0u32.to_le_bytes());
+
+    let node = Node::read_from(&data).unwrap();
+    let CGame::CtnGhost(ghost) = node.parse().unwrap() else {
+        panic!("not a ghost");
+    };
+    assert_eq!(ghost.events_duration, Some(0));
+    assert!(ghost.inputs.is_empty());
+    assert_eq!(ghost.game_version.as_deref(), Some("3.3.0"));
+}
@@ -0,0 +1,72 @@
+//! Parsing items
+
+mod common;
+
+use common::test_data;
+use gbx_rs::{parse::CGame, ItemModelKind, ItemType, Node, NodeRef, WaypointType};
+
+/// A checkpoint item from the Stadium collection
+const CHECKPOINT: &str = "Checkpoint.Item.Gbx";
+
+#[test]
+fn checkpoint_item() {
+    let data = test_data(CHECKPOINT);
+    let node = Node::read_from(&data).unwrap();
+    let (parsed, report) = node.parse_with_report().unwrap();
+    let CGame::ItemModel(item) = parsed else {
+        panic!("not an item");
+    };
+
+    assert_eq!(item.item_type, Some(ItemType::Ornament));
+    assert_eq!(item.waypoint_type, Some(WaypointType::Checkpoint));
+    assert_eq!(item.kind, Some(ItemModelKind::Mesh));
+    assert_eq!(item.entity_model_class, Some(0x2e027000));
+    assert_eq!(item.item_info.unwrap().collection, "Stadium");
+    let placement = item.placement.as_ref().and_then(NodeRef::local).unwrap();
+    assert_eq!(placement.pivot_positions, [[16.0, 2.0, 16.0]]);
+
+    // the entity model isn't parsed, but the chunks after it are
+    let force_skipped: Vec<_> = report
+        .force_skipped_chunks
+        .iter()
+        .map(|chunk| chunk.chunk_id)
+        .collect();
+    assert_eq!(force_skipped, [0x2e002019]);
+}
+
+#[test]
+fn item_header_chunks_keep_their_class() {
+    let data = test_data(CHECKPOINT);
+    let node = Node::read_header(&data).unwrap();
+    let chunk = &node.header().chunks[0];
+    assert_eq!(chunk.id, chunk.full_id & 0xfff);
+    // stored with the class ID of CGameCtnCollector
+    assert_eq!(chunk.full_id & 0xffff_f000, 0x2e001000);
+}
+
+#[test]
+fn null_entity_models() {
+    let original = test_data(CHECKPOINT);
+    let node = Node::read_from(&original).unwrap();
+    let (_, report) = node.parse_with_report().unwrap();
+    // stored uncompressed, at the end of the file
+    let body_start = original.len() - node.body().unwrap().len();
+
+    // the skipped model starts after its index and class ID, and is replaced by a null reference
+    let skipped = &report.force_skipped_chunks[0];
+    let start = body_start + skipped.offset as usize - 8;
+    let end = body_start + skipped.offset as usize + skipped.size as usize;
+    let mut data = original[..start].to_vec();
+    data.extend_from_slice(&(-1i32).to_le_bytes());
+    data.extend_from_slice(&original[end..]);
+
+    let node = Node::read_from(&data).unwrap();
+    let (parsed, report) = node.parse_with_report().unwrap();
+    let CGame::ItemModel(item) = parsed else {
+        panic!("not an item");
+    };
+    assert_eq!(item.kind, None);
+    assert_eq!(item.entity_model_class, None);
+    assert!(report.force_skipped_chunks.is_empty());
+    assert!(item.placement.is_some());
+}
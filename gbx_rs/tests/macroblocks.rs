@@ -0,0 +1,107 @@
+//! Parsing macroblocks
+
+mod common;
+
+use common::test_data;
+use gbx_rs::{
+    parse::{CGame, CtnMacroBlockInfo},
+    Direction, Node,
+};
+
+/// A macroblock with a block on the grid, a free block and an item
+const MACROBLOCK: &str = "Test.Macroblock.Gbx";
+
+fn parse_macroblock<'node>(node: &'node Node) -> CtnMacroBlockInfo<'node> {
+    match node.parse().unwrap() {
+        CGame::CtnMacroBlockInfo(macroblock) => macroblock,
+        _ => panic!("not a macroblock"),
+    }
+}
+
+#[test]
+fn macroblock() {
+    let data = test_data(MACROBLOCK);
+    let node = Node::read_from(&data).unwrap();
+    let macroblock = parse_macroblock(&node);
+
+    assert_eq!(macroblock.name.as_deref(), Some("My Macro"));
+    assert!(macroblock.icon.unwrap().webp);
+
+    let [grid, free] = &macroblock.blocks[..] else {
+        panic!("expected two blocks");
+    };
+    assert_eq!(grid.name, "RoadTechStraight");
+    assert_eq!(grid.direction, Some(Direction::East));
+    assert_eq!(grid.position, [1, 0, 2]);
+    assert!(grid.is_ground);
+    assert_eq!(grid.free_position, None);
+
+    assert!(free.is_free);
+    assert_eq!(free.free_position, Some([1.0, 2.0, 3.0]));
+    assert_eq!(free.free_pitch_yaw_roll, Some([0.0, 0.0, 0.0]));
+
+    let item_model = macroblock.items[0].item_model.as_ref().unwrap();
+    assert_eq!(item_model.id, "Flag.Item.Gbx");
+    assert_eq!(macroblock.items[0].position, Some([32.0, 8.0, 32.0]));
+}
+
+#[test]
+fn external_block_skins_are_kept() {
+    let data = test_data("External.Macroblock.Gbx");
+    let node = Node::read_from(&data).unwrap();
+    let block = &parse_macroblock(&node).blocks[0];
+
+    assert_eq!(block.author.as_deref(), Some("skinner"));
+    let skin = block.skin.as_ref().unwrap();
+    assert!(skin.local().is_none());
+    let external_ref = skin.external().unwrap();
+    assert_eq!(external_ref.node_index, 5);
+    assert_eq!(external_ref.path().as_deref(), Some("Skins/Any/Pack.Gbx"));
+}
+
+/// Finds the first block's direction, position and flags
+fn first_block(data: &[u8]) -> usize {
+    let fields: Vec<u8> = [1u32, 1, 0, 2, 0x1000]
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+    data.windows(fields.len())
+        .position(|window| window == fields)
+        .unwrap()
+}
+
+#[test]
+fn unknown_block_directions_are_not_truncated() {
+    let mut data = test_data(MACROBLOCK);
+    let at = first_block(&data);
+    data[at + 1] = 1;
+
+    let node = Node::read_from(&data).unwrap();
+    assert_eq!(parse_macroblock(&node).blocks[0].direction, None);
+}
+
+#[test]
+fn block_coords_outside_maps_fail() {
+    let mut data = test_data(MACROBLOCK);
+    let at = first_block(&data) + 4;
+    data[at + 1] = 1;
+
+    let node = Node::read_from(&data).unwrap();
+    let err = node.parse().unwrap_err();
+    assert!(err.to_string().contains("Block coordinate 257"), "{}", err);
+}
+
+#[test]
+fn blocks_without_flags_are_left_out() {
+    let mut data = test_data(MACROBLOCK);
+    let at = first_block(&data) + 16;
+    data[at..at + 4].copy_from_slice(&0xffff_ffffu32.to_le_bytes());
+
+    let node = Node::read_from(&data).unwrap();
+    let macroblock = parse_macroblock(&node);
+    let [free] = &macroblock.blocks[..] else {
+        panic!("expected one block");
+    };
+    assert!(free.is_free);
+    assert_eq!(free.free_position, Some([1.0, 2.0, 3.0]));
+}
@@ -0,0 +1,164 @@
+//! Malformed input has to fail with an error, never a panic
+
+mod common;
+
+use common::{example_map, gbx_file, gbx_file_with_refs, gbx_string, Rng, EXAMPLE_MAPS};
+use gbx_rs::{GbxErrorInner, Node, ParseLimits};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+fn parse_everything(data: &[u8]) {
+    if let Ok(node) = Node::read_from(&data) {
+        let _ = node.parse();
+        let _ = node.parse_header();
+        let _ = node.body_chunks();
+    }
+    if let Ok(node) = Node::read_stream(std::io::Cursor::new(data)) {
+        let _ = node.parse();
+    }
+}
+
+fn assert_no_panic(data: &[u8], what: &str) {
+    if catch_unwind(AssertUnwindSafe(|| parse_everything(data))).is_err() {
+        panic!("parsing {} panicked", what);
+    }
+}
+
+#[test]
+fn truncated_maps() {
+    for name in EXAMPLE_MAPS {
+        let original = example_map(name);
+        for end in (0..original.len()).step_by(original.len() / 150) {
+            assert_no_panic(&original[..end], &format!("{} cut at {}", name, end));
+        }
+    }
+}
+
+#[test]
+fn corrupt_maps() {
+    let mut rng = Rng(0x1234_5678);
+    for name in EXAMPLE_MAPS {
+        let original = example_map(name);
+        for i in 0..300 {
+            let mut data = original.clone();
+            match i % 3 {
+                0 => {
+                    for _ in 0..1 + rng.below(8) {
+                        let position = rng.below(data.len());
+                        data[position] = rng.next() as u8;
+                    }
+                }
+                1 => {
+                    let position = rng.below(data.len() - 4);
+                    let value = [0xffff_ffffu32, 0x7fff_ffff, 0x8000_0000, 0][rng.below(4)];
+                    data[position..position + 4].copy_from_slice(&value.to_le_bytes());
+                }
+                _ => {
+                    let position = rng.below(data.len());
+                    data.insert(position, rng.next() as u8);
+                }
+            }
+            assert_no_panic(&data, &format!("{} mutation {}", name, i));
+        }
+    }
+}
+
+#[test]
+fn corrupt_compressed_bodies() {
+    // back-references before the start of the output, and runs past the end of the input
+    for body in [
+        &[0x20, 0x00, 0xff, 0xff, 0xff][..],
+        &[0x15, b'a', b'b', b'c', b'd', 0x40, 0xff, 0x11, 0x00, 0x00],
+        &[0x01, 0x02],
+        &[0x16],
+        &[0x11, 0x04, 0x00],
+        &[],
+    ] {
+        let data = gbx_file(0x03043000, true, 16, body);
+        assert_no_panic(&data, &format!("body {:02x?}", body));
+        assert!(Node::read_from(&data).is_err(), "body {:02x?}", body);
+    }
+}
+
+#[test]
+fn decompressed_size_is_limited_while_decompressing() {
+    // four literals, then one back-reference of about 25 MB, while the file claims 16 bytes
+    let mut body = vec![0x15, b'a', b'a', b'a', b'a', 0x20];
+    body.resize(body.len() + 100_000, 0);
+    body.extend_from_slice(&[0xff, 0x00, 0x00, 0x11, 0x00, 0x00]);
+    let data = gbx_file(0x03043000, true, 16, &body);
+
+    let mut limits = ParseLimits::default();
+    limits.max_decompressed_size = 1 << 20;
+    let node = Node::read_header(&data).unwrap().with_limits(limits);
+    let err = node.body().unwrap_err();
+    assert!(
+        matches!(*err, GbxErrorInner::DecompressedSizeLimit { limit, .. } if limit == 1 << 20),
+        "{}",
+        err
+    );
+
+    // without the limit, it decompresses to what the data says
+    let node = Node::read_header(&data).unwrap();
+    assert_eq!(node.body().unwrap().len(), 4 + 100_000 * 255 + 255 + 31 + 2);
+
+    // and with it, from nodes read all at once
+    let err = Node::read_from_with_limits(&data, limits).unwrap_err();
+    assert!(
+        matches!(*err, GbxErrorInner::DecompressedSizeLimit { .. }),
+        "{}",
+        err
+    );
+    let err = Node::read_stream_with_limits(std::io::Cursor::new(&data), limits).unwrap_err();
+    assert!(
+        matches!(*err, GbxErrorInner::DecompressedSizeLimit { .. }),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn streamed_uncompressed_size_is_limited() {
+    let body = vec![0; 1 << 10];
+    let data = gbx_file(0x03043000, false, 0, &body);
+
+    let mut limits = ParseLimits::default();
+    limits.max_decompressed_size = body.len() - 1;
+    let err = Node::read_stream_with_limits(std::io::Cursor::new(&data), limits).unwrap_err();
+    assert!(
+        matches!(*err, GbxErrorInner::DecompressedSizeLimit { limit, .. } if limit == body.len() - 1),
+        "{}",
+        err
+    );
+
+    limits.max_decompressed_size = body.len();
+    let node = Node::read_stream_with_limits(std::io::Cursor::new(&data), limits).unwrap();
+    assert_eq!(node.body().unwrap(), body);
+}
+
+#[test]
+fn ref_table_folders_are_limited() {
+    // 40 nested folders, with one external node in the innermost
+    let mut ref_table = 1u32.to_le_bytes().to_vec();
+    for _ in 0..40 {
+        ref_table.extend_from_slice(&1u32.to_le_bytes());
+        ref_table.extend(gbx_string("a"));
+    }
+    ref_table.extend_from_slice(&0u32.to_le_bytes());
+    ref_table.extend_from_slice(&0u32.to_le_bytes());
+    ref_table.extend(gbx_string("Inner.Gbx"));
+    ref_table.extend_from_slice(&1u32.to_le_bytes());
+    ref_table.extend_from_slice(&0u32.to_le_bytes());
+    ref_table.extend_from_slice(&40u32.to_le_bytes());
+    let data = gbx_file_with_refs(0x03043000, false, 0, 1, &ref_table, &[]);
+
+    let node = Node::read_header(&data).unwrap();
+    let err = node.parse_header().unwrap_err();
+    assert!(matches!(*err, GbxErrorInner::DepthLimit(32)), "{}", err);
+
+    let mut limits = ParseLimits::default();
+    limits.max_depth = 40;
+    let node = Node::read_header(&data).unwrap().with_limits(limits);
+    node.parse_header().unwrap();
+    let folder = node.header().external_refs[0].folder.as_deref().unwrap();
+    assert_eq!(folder, vec!["a"; 40].join("/"));
+}
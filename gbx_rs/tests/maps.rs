@@ -0,0 +1,164 @@
+//! Parsing the bundled maps
+
+mod common;
+
+use common::{example_map, parse_map, uncompressed, ARCTIC_LUNGUS};
+use gbx_rs::{
+    parse::{CGame, CtnChallenge},
+    GbxErrorInner, Node, NodeRef, ParseLimits, PlayMode,
+};
+use std::io::Write;
+
+#[test]
+fn unknown_play_modes_are_not_truncated() {
+    let mut data = example_map(ARCTIC_LUNGUS);
+    let node = Node::read_from(&data).unwrap();
+    assert_eq!(parse_map(&node).play_mode, Some(PlayMode::Race));
+
+    // version 13 of chunk 002: version, unknown, four medal times, cost, lap race, play mode
+    let chunk = node
+        .header()
+        .chunks
+        .iter()
+        .find(|chunk| chunk.full_id == 0x03043002);
+    let chunk_data = node.header_chunk_data(chunk.unwrap()).unwrap();
+    assert_eq!(chunk_data[0], 13);
+    let position = data
+        .windows(chunk_data.len())
+        .position(|window| window == chunk_data)
+        .unwrap()
+        + 29;
+    assert_eq!(data[position..position + 4], [0, 0, 0, 0]);
+
+    data[position + 1] = 1;
+    let node = Node::read_from(&data).unwrap();
+    let map = parse_map(&node);
+    assert_eq!(map.play_mode, None);
+    // and the original value is written back
+    assert!(node.write_challenge(&map).unwrap() == data);
+}
+
+#[test]
+fn media_clips() {
+    let data = example_map(ARCTIC_LUNGUS);
+    let node = Node::read_from(&data).unwrap();
+    let (CGame::CtnChallenge(map), report) = node.parse_with_report().unwrap() else {
+        panic!("not a map");
+    };
+    assert!(report.force_skipped_chunks.is_empty());
+
+    // a camera, a text and the ghost of the author
+    let group = map.clip_group_in_game.as_ref().and_then(NodeRef::local);
+    let blocks: Vec<_> = group.unwrap().clips[0]
+        .local()
+        .unwrap()
+        .tracks
+        .iter()
+        .filter_map(NodeRef::local)
+        .flat_map(|track| track.blocks.iter().map(CGame::class_id))
+        .collect();
+    assert_eq!(blocks, [0x03084000, 0x030a8000, 0x0329f000]);
+}
+
+#[test]
+fn media_clips_keep_to_the_limits() {
+    // the clips are nested in a group, so they're deeper than any other node
+    let data = example_map(ARCTIC_LUNGUS);
+    let mut limits = ParseLimits::default();
+    limits.max_depth = 2;
+    let node = Node::read_from(&data).unwrap().with_limits(limits);
+    let err = node.parse().unwrap_err();
+    assert!(matches!(*err, GbxErrorInner::DepthLimit(2)), "{}", err);
+    assert_eq!(err.chunk_stack()[0], 0x03043049);
+}
+
+#[test]
+fn unknown_media_block_versions_are_reported() {
+    let (header, mut body) = uncompressed(&example_map(ARCTIC_LUNGUS));
+    // version 4 is the only one of game camera chunk 007 that is known, and it isn't skippable
+    let camera = [0x03084007u32.to_le_bytes(), 4u32.to_le_bytes()].concat();
+    let position = body.windows(8).position(|window| window == camera).unwrap();
+    body[position + 4] = 5;
+
+    let data = [header, body].concat();
+    let node = Node::read_from(&data).unwrap();
+    let (CGame::CtnChallenge(map), report) = node.parse_with_report().unwrap() else {
+        panic!("not a map");
+    };
+    assert!(map.clip_group_in_game.is_none());
+    let chunk_ids: Vec<_> = report
+        .force_skipped_chunks
+        .iter()
+        .map(|chunk| chunk.chunk_id)
+        .collect();
+    assert_eq!(chunk_ids, [0x03043049]);
+    // the rest of the map is still read
+    assert_eq!(map.blocks.len(), 22);
+}
+
+#[test]
+fn binary_fields_survive_json() {
+    let data = example_map(ARCTIC_LUNGUS);
+    let node = Node::read_from(&data).unwrap();
+    let map = parse_map(&node);
+    let thumbnail = map.thumbnail_data.as_deref().unwrap();
+
+    let json = serde_json::to_value(&map).unwrap();
+    // base64, rather than an array of numbers
+    assert!(json["thumbnail_data"].is_string());
+
+    let read: CtnChallenge = serde_json::from_value(json).unwrap();
+    assert_eq!(read.thumbnail_data.as_deref(), Some(thumbnail));
+    assert_eq!(read.embedded_zip, map.embedded_zip);
+}
+
+/// A zip archive of `size` zeros, claiming to hold `claimed_size` of them
+fn zeros_zip(size: usize, claimed_size: u32) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file(
+        "Items/Zeros.Item.Gbx",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    zip.write_all(&vec![0; size]).unwrap();
+    let mut data = zip.finish().unwrap().into_inner();
+
+    // the sizes in the local file header and the central directory
+    let local = 0;
+    let central = data.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+    for at in [local + 22, central + 24] {
+        data[at..at + 4].copy_from_slice(&claimed_size.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn embedded_files_are_limited() {
+    let data = example_map(ARCTIC_LUNGUS);
+    let node = Node::read_from(&data).unwrap();
+    let mut map = parse_map(&node);
+    let mut limits = ParseLimits::default();
+    limits.max_decompressed_size = 1 << 20;
+
+    map.embedded_zip = Some(zeros_zip(1 << 20, 1 << 20).into());
+    let files = map.embedded_files_with_limits(&limits).unwrap();
+    assert_eq!(files[0].1.len(), 1 << 20);
+
+    // too large, according to the archive
+    map.embedded_zip = Some(zeros_zip(2 << 20, 2 << 20).into());
+    let err = map.embedded_files_with_limits(&limits).unwrap_err();
+    assert!(
+        matches!(*err, GbxErrorInner::DecompressedSizeLimit { .. }),
+        "{}",
+        err
+    );
+
+    // too large, while the archive claims it isn't
+    map.embedded_zip = Some(zeros_zip(2 << 20, 16).into());
+    let err = map.embedded_files_with_limits(&limits).unwrap_err();
+    assert!(
+        matches!(*err, GbxErrorInner::DecompressedSizeLimit { .. }),
+        "{}",
+        err
+    );
+}
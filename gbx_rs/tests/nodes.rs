@@ -0,0 +1,76 @@
+//! Node references, which index nodes throughout the file
+
+mod common;
+
+use common::{gbx_file, gbx_string};
+use gbx_rs::{parse::CGame, Node, NodeRef};
+use std::sync::Arc;
+
+/// A CtnMediaClip node with a single track, `track` being its node reference
+fn clip(index: i32, track: &[u8], name: &str) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&index.to_le_bytes());
+    data.extend_from_slice(&0x03079000u32.to_le_bytes());
+    data.extend_from_slice(&0x0307900du32.to_le_bytes());
+    // version, track list version, number of tracks
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&10u32.to_le_bytes());
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(track);
+    data.extend_from_slice(&gbx_string(name));
+    // stop when leave, unknown, stop when respawn
+    for _ in 0..3 {
+        data.extend_from_slice(&0u32.to_le_bytes());
+    }
+    data.extend_from_slice(&gbx_string(""));
+    data.extend_from_slice(&0f32.to_le_bytes());
+    data.extend_from_slice(&(-1i32).to_le_bytes());
+    data.extend_from_slice(&0xfacade01u32.to_le_bytes());
+    data
+}
+
+#[test]
+fn nested_nodes_are_shared() {
+    // the track is read inside the first clip, and referenced again by the second
+    let mut track = Vec::new();
+    track.extend_from_slice(&2i32.to_le_bytes());
+    track.extend_from_slice(&0x03078000u32.to_le_bytes());
+    track.extend_from_slice(&0x03078001u32.to_le_bytes());
+    track.extend_from_slice(&gbx_string("Track"));
+    // block list version, number of blocks, unknown
+    track.extend_from_slice(&10u32.to_le_bytes());
+    track.extend_from_slice(&0u32.to_le_bytes());
+    track.extend_from_slice(&0u32.to_le_bytes());
+    track.extend_from_slice(&0xfacade01u32.to_le_bytes());
+
+    let mut body = Vec::new();
+    body.extend_from_slice(&0x0307a003u32.to_le_bytes());
+    // clip list version, number of clips
+    body.extend_from_slice(&10u32.to_le_bytes());
+    body.extend_from_slice(&2u32.to_le_bytes());
+    body.extend_from_slice(&clip(1, &track, "First"));
+    body.extend_from_slice(&clip(3, &2i32.to_le_bytes(), "Second"));
+    // number of triggers
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&0xfacade01u32.to_le_bytes());
+    let data = gbx_file(0x0307a000, false, 0, &body);
+
+    let node = Node::read_from(&data).unwrap();
+    let CGame::CtnMediaClipGroup(group) = node.parse().unwrap() else {
+        panic!("not a clip group");
+    };
+
+    let [first, second] = &group.clips[..] else {
+        panic!("{} clips", group.clips.len());
+    };
+    let (first, second) = (first.local().unwrap(), second.local().unwrap());
+    assert_eq!(first.name.as_deref(), Some("First"));
+    assert_eq!(second.name.as_deref(), Some("Second"));
+    let (NodeRef::Local(first_track), NodeRef::Local(second_track)) =
+        (&first.tracks[0], &second.tracks[0])
+    else {
+        panic!("external tracks");
+    };
+    assert!(Arc::ptr_eq(first_track, second_track));
+    assert_eq!(first_track.name.as_deref(), Some("Track"));
+}
@@ -0,0 +1,97 @@
+//! Chunk handlers registered at runtime
+
+mod common;
+
+use common::{example_map, gbx_file, ARCTIC_LUNGUS};
+use gbx_rs::{parse::CGame, registry::ChunkRegistry, Node};
+
+#[test]
+fn body_chunk_handlers() {
+    let data = example_map(ARCTIC_LUNGUS);
+    let mut registry = ChunkRegistry::new();
+    registry
+        .register_chunk(0x03043056, |reader| {
+            let version = reader.read_u32()?;
+            Ok((version, reader.read_to_chunk_end()?.len()))
+        })
+        .register_raw_chunk(0x03043064);
+    let node = Node::read_from(&data).unwrap().with_registry(registry);
+    let CGame::CtnChallenge(map) = node.parse().unwrap() else {
+        panic!("not a map");
+    };
+
+    // 24 bytes, of which the version is the first 4
+    assert_eq!(
+        map.extensions.get::<(u32, usize)>(0x03043056).unwrap().1,
+        20
+    );
+    assert_eq!(map.extensions.get::<Vec<u8>>(0x03043064).unwrap().len(), 16);
+    assert!(map.extensions.get::<u32>(0x03043056).is_none());
+    assert!(!map.extensions.contains(0x03043065));
+}
+
+#[test]
+fn header_chunk_handlers() {
+    let data = example_map(ARCTIC_LUNGUS);
+    let mut registry = ChunkRegistry::new();
+    registry.register_raw_chunk(0x03043008);
+    let node = Node::read_header(&data).unwrap().with_registry(registry);
+    let CGame::CtnChallenge(map) = node.parse_header().unwrap() else {
+        panic!("not a map");
+    };
+
+    let chunk = node
+        .header()
+        .chunks
+        .iter()
+        .find(|chunk| chunk.full_id == 0x03043008);
+    let expected = node.header_chunk_data(chunk.unwrap()).unwrap();
+    assert_eq!(map.extensions.get::<Vec<u8>>(0x03043008).unwrap(), expected);
+}
+
+#[test]
+fn registered_classes() {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0x0a0b0001u32.to_le_bytes());
+    body.extend_from_slice(&42u32.to_le_bytes());
+    body.extend_from_slice(&0x0a0b0002u32.to_le_bytes());
+    body.extend_from_slice(b"PIKS");
+    body.extend_from_slice(&3u32.to_le_bytes());
+    body.extend_from_slice(b"abc");
+    // skippable, without a handler
+    body.extend_from_slice(&0x0a0b0003u32.to_le_bytes());
+    body.extend_from_slice(b"PIKS");
+    body.extend_from_slice(&1u32.to_le_bytes());
+    body.push(0);
+    body.extend_from_slice(&0xfacade01u32.to_le_bytes());
+    let data = gbx_file(0x0a0b0000, false, 0, &body);
+
+    let mut registry = ChunkRegistry::new();
+    registry
+        .register_class(0x0a0b0000)
+        .register_chunk(0x0a0b0001, |reader| reader.read_u32())
+        .register_raw_chunk(0x0a0b0002);
+    let node = Node::read_from(&data).unwrap().with_registry(registry);
+    let (CGame::Extension(extension), report) = node.parse_with_report().unwrap() else {
+        panic!("not an extension node");
+    };
+
+    assert_eq!(extension.class_id, 0x0a0b0000);
+    assert_eq!(extension.extensions.get::<u32>(0x0a0b0001), Some(&42));
+    assert_eq!(
+        extension.extensions.get::<Vec<u8>>(0x0a0b0002).unwrap(),
+        b"abc"
+    );
+    assert_eq!(report.skipped_chunks.len(), 2);
+    assert!(report
+        .skipped_chunks
+        .iter()
+        .any(|chunk| chunk.chunk_id == 0x0a0b0003 && !chunk.header));
+}
+
+#[test]
+fn unregistered_classes_fail() {
+    let data = gbx_file(0x0a0b0000, false, 0, &0xfacade01u32.to_le_bytes());
+    let node = Node::read_from(&data).unwrap();
+    assert!(node.parse().is_err());
+}
@@ -0,0 +1,110 @@
+//! Parsing replays, which embed the map they were driven on
+
+mod common;
+
+use common::{example_map, gbx_file, gbx_string, QUEST_FOR_THE_THIRD_LUNGUS};
+use gbx_rs::{parse::CGame, GbxErrorInner, Node, ParseLimits};
+
+/// A replay of `map` with one ghost, followed by the chunks in `extra`
+fn replay(map: &[u8], extra: &[u8]) -> Vec<u8> {
+    let mut body = 0x03093002u32.to_le_bytes().to_vec();
+    body.extend_from_slice(&(map.len() as u32).to_le_bytes());
+    body.extend_from_slice(map);
+
+    // version, one ghost with a race time and login, unknown, no extras
+    body.extend_from_slice(&0x03093014u32.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&1u32.to_le_bytes());
+    body.extend_from_slice(&1u32.to_le_bytes());
+    body.extend_from_slice(&0x03092000u32.to_le_bytes());
+    body.extend_from_slice(&0x03092005u32.to_le_bytes());
+    body.extend_from_slice(&52340u32.to_le_bytes());
+    body.extend_from_slice(&0x0309200fu32.to_le_bytes());
+    body.extend(gbx_string("driver"));
+    body.extend_from_slice(&0xfacade01u32.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes());
+
+    body.extend_from_slice(extra);
+    body.extend_from_slice(&0xfacade01u32.to_le_bytes());
+    gbx_file(0x03093000, false, 0, &body)
+}
+
+#[test]
+fn replay_map_and_ghosts() {
+    let map = example_map(QUEST_FOR_THE_THIRD_LUNGUS);
+    let data = replay(&map, &[]);
+    let node = Node::read_from(&data).unwrap();
+    let CGame::CtnReplayRecord(replay) = node.parse().unwrap() else {
+        panic!("not a replay");
+    };
+
+    let embedded = replay.map.as_deref().unwrap();
+    assert_eq!(
+        embedded.map_name.as_deref(),
+        Some("Quest for the Third Lungus")
+    );
+    assert_eq!(replay.map_data.as_deref(), Some(&map[..]));
+
+    let [ghost] = &replay.ghosts[..] else {
+        panic!("expected one ghost");
+    };
+    let ghost = ghost.local().unwrap();
+    assert_eq!(ghost.race_time, Some(52340));
+    assert_eq!(ghost.login.as_deref(), Some("driver"));
+}
+
+#[test]
+fn replay_maps_are_parsed_within_the_limits() {
+    let data = replay(&example_map(QUEST_FOR_THE_THIRD_LUNGUS), &[]);
+    let mut limits = ParseLimits::default();
+    limits.max_decompressed_size = 100_000;
+
+    // the replay's own body isn't compressed, so only the map's is over the limit
+    let node = Node::read_from(&data).unwrap().with_limits(limits);
+    let err = node.parse().unwrap_err();
+    assert!(
+        matches!(*err, GbxErrorInner::DecompressedSizeLimit { limit, .. } if limit == 100_000),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn unknown_replay_chunks_fail() {
+    // a chunk of older replays that isn't supported, and not skippable
+    let mut chunk = 0x0309300du32.to_le_bytes().to_vec();
+    chunk.extend_from_slice(&0u32.to_le_bytes());
+    let data = replay(&example_map(QUEST_FOR_THE_THIRD_LUNGUS), &chunk);
+
+    let node = Node::read_from(&data).unwrap();
+    let err = node.parse().unwrap_err();
+    assert!(
+        matches!(
+            *err,
+            GbxErrorInner::InvalidChunkForClass {
+                chunk_id: 0x0309300d,
+                class_id: 0x03093000
+            }
+        ),
+        "{}",
+        err
+    );
+
+    // skippable chunks are skipped, and reported
+    let mut chunk = 0x0309300du32.to_le_bytes().to_vec();
+    chunk.extend_from_slice(b"PIKS");
+    chunk.extend_from_slice(&4u32.to_le_bytes());
+    chunk.extend_from_slice(&0u32.to_le_bytes());
+    let data = replay(&example_map(QUEST_FOR_THE_THIRD_LUNGUS), &chunk);
+
+    let node = Node::read_from(&data).unwrap();
+    let (parsed, report) = node.parse_with_report().unwrap();
+    assert!(matches!(parsed, CGame::CtnReplayRecord(_)));
+    let skipped: Vec<_> = report
+        .skipped_chunks
+        .iter()
+        .map(|chunk| chunk.chunk_id)
+        .collect();
+    assert!(skipped.contains(&0x0309300d), "{:?}", skipped);
+}
@@ -0,0 +1,138 @@
+//! Writing maps back with `Node::write_challenge`
+
+mod common;
+
+use common::{example_map, parse_map, EXAMPLE_MAPS};
+use gbx_rs::{GbxErrorInner, Node};
+
+/// Where the body sizes start, which is where the header of both files ends
+fn body_start(data: &[u8]) -> usize {
+    let body_size = |start: usize| u32::from_le_bytes(data[start..start + 4].try_into().unwrap());
+    (0..data.len() - 8)
+        .find(|&start| body_size(start + 4) as usize == data.len() - start - 8)
+        .unwrap()
+}
+
+#[test]
+fn unchanged_maps_are_identical() {
+    for name in EXAMPLE_MAPS {
+        let data = example_map(name);
+        let node = Node::read_from(&data).unwrap();
+        let written = node.write_challenge(&parse_map(&node)).unwrap();
+        assert!(written == data, "{}", name);
+    }
+}
+
+#[test]
+fn recompressed_maps_are_identical() {
+    for name in EXAMPLE_MAPS {
+        let data = example_map(name);
+        // streamed nodes don't keep the compressed body, so it is compressed again
+        let node = Node::read_stream(std::io::Cursor::new(&data)).unwrap();
+        let written = node.write_challenge(&parse_map(&node)).unwrap();
+
+        let header_end = body_start(&data);
+        assert_eq!(body_start(&written), header_end, "{}", name);
+        assert!(written[..header_end] == data[..header_end], "{}", name);
+        let written_node = Node::read_from(&written).unwrap();
+        assert!(
+            written_node.body().unwrap() == node.body().unwrap(),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn edited_maps_parse_back() {
+    for name in EXAMPLE_MAPS {
+        let data = example_map(name);
+        let node = Node::read_from(&data).unwrap();
+        let map = parse_map(&node);
+
+        let mut edited = map.clone();
+        edited.map_name = Some("Round-trip test".into());
+        edited.author_time = Some(12_345);
+        // kept as it was
+        edited.gold_time = None;
+        edited.password = Some("".into());
+        let written = node.write_challenge(&edited).unwrap();
+
+        let written_node = Node::read_from(&written).unwrap();
+        let written_map = parse_map(&written_node);
+        assert_eq!(written_map.map_name.as_deref(), Some("Round-trip test"));
+        assert_eq!(written_map.author_time, Some(12_345));
+        assert_eq!(written_map.gold_time, map.gold_time);
+        assert_eq!(written_map.password.as_deref(), Some(""));
+        assert!(written_map.blocks == map.blocks, "{}", name);
+        assert!(written_map.items == map.items, "{}", name);
+    }
+}
+
+#[test]
+fn unsupported_edits_fail() {
+    let data = example_map(EXAMPLE_MAPS[0]);
+    let node = Node::read_from(&data).unwrap();
+    let map = parse_map(&node);
+
+    let mut edited = map.clone();
+    edited.blocks.pop();
+    let err = node.write_challenge(&edited).unwrap_err();
+    assert!(
+        matches!(*err, GbxErrorInner::WriteUnsupportedField("blocks")),
+        "{}",
+        err
+    );
+
+    let mut edited = map.clone();
+    edited.clip_trigger_size = Some([1, 2, 3]);
+    let err = node.write_challenge(&edited).unwrap_err();
+    assert!(
+        matches!(
+            *err,
+            GbxErrorInner::WriteUnsupportedField("clip_trigger_size")
+        ),
+        "{}",
+        err
+    );
+
+    // negative times are read as no time
+    let mut edited = map.clone();
+    edited.bronze_time = Some(-5);
+    let err = node.write_challenge(&edited).unwrap_err();
+    assert!(
+        matches!(*err, GbxErrorInner::WriteUnstoredField("bronze_time")),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn collection_ids_are_kept() {
+    let mut data = example_map(EXAMPLE_MAPS[0]);
+    let node = Node::read_header(&data).unwrap();
+    let uid = parse_map(&node).map_info.unwrap().id.into_owned();
+    // in the header, the map's collection is stored as an ID right after its UID
+    let position = data
+        .windows(uid.len())
+        .position(|window| window == uid.as_bytes())
+        .unwrap()
+        + uid.len();
+    let collection_id =
+        |data: &[u8]| u32::from_le_bytes(data[position..position + 4].try_into().unwrap());
+    assert_eq!(collection_id(&data), 26);
+
+    // Stadium has an older ID as well, which is kept
+    data[position] = 6;
+    let node = Node::read_from(&data).unwrap();
+    let map = parse_map(&node);
+    assert_eq!(map.map_info.as_ref().unwrap().collection, "Stadium");
+    let written = node.write_challenge(&map).unwrap();
+    assert_eq!(collection_id(&written), 6);
+
+    // other collections get their own ID
+    let mut edited = map.clone();
+    edited.map_info.as_mut().unwrap().collection = "Valley".into();
+    let written = node.write_challenge(&edited).unwrap();
+    assert_eq!(collection_id(&written), 11);
+}
@@ -0,0 +1,73 @@
+//! Reading files with `Node::read_stream`, which has to find where the header ends by itself
+
+mod common;
+
+use common::{gbx_file_with_refs, gbx_string};
+use gbx_rs::Node;
+use std::io::Cursor;
+
+/// A text ghost with a header chunk and an external node in a folder, whose body has a race time
+/// and a login. Header chunk data starts right after the header, so the line break is part of it.
+const TEXT_GHOST: &[u8] = b"GBX\x06\x00TUUR 50929664 0 1\r\n\
+50933759 6\r\nabcd\r\n\
+2 1\r\n\
+1\r\n1\r\n6 Folder 0\r\n\
+0 9 Inner.Gbx 3 1 1\r\n\
+50929669 12345\r\n50929679 5 login\r\n4207599105\r\n";
+
+#[test]
+fn text_files_are_streamed() {
+    let streamed = Node::read_stream(Cursor::new(TEXT_GHOST)).unwrap();
+    let read = Node::read_from(&TEXT_GHOST).unwrap();
+
+    assert_eq!(streamed.header().num_nodes, 2);
+    assert_eq!(streamed.header().external_refs, read.header().external_refs);
+    let folder = streamed.header().external_refs[0].folder.as_deref();
+    assert_eq!(folder, Some("Folder"));
+    assert_eq!(streamed.body().unwrap(), read.body().unwrap());
+    assert_eq!(streamed.parse().unwrap(), read.parse().unwrap());
+}
+
+#[test]
+fn external_refs_are_streamed() {
+    // a folder with a subfolder, and a file in each
+    let mut ref_table = 1u32.to_le_bytes().to_vec();
+    ref_table.extend_from_slice(&1u32.to_le_bytes());
+    ref_table.extend(gbx_string("Outer"));
+    ref_table.extend_from_slice(&1u32.to_le_bytes());
+    ref_table.extend(gbx_string("Inner"));
+    ref_table.extend_from_slice(&0u32.to_le_bytes());
+    for (name, folder_index) in [("A.Gbx", 1u32), ("B.Gbx", 2)] {
+        ref_table.extend_from_slice(&0u32.to_le_bytes());
+        ref_table.extend(gbx_string(name));
+        ref_table.extend_from_slice(&folder_index.to_le_bytes());
+        ref_table.extend_from_slice(&0u32.to_le_bytes());
+        ref_table.extend_from_slice(&folder_index.to_le_bytes());
+    }
+
+    let body = b"not parsed, only decompressed".repeat(10);
+    let compressed = lzokay_native::compress(&body).unwrap();
+    let mut data = gbx_file_with_refs(
+        0x03043000,
+        true,
+        body.len() as u32,
+        2,
+        &ref_table,
+        &compressed,
+    );
+    // after the body, so it shouldn't end up in it
+    data.extend_from_slice(b"trailing");
+
+    let streamed = Node::read_stream(Cursor::new(&data)).unwrap();
+    let read = Node::read_from(&data).unwrap();
+    assert_eq!(streamed.header().external_refs, read.header().external_refs);
+    let folders: Vec<_> = streamed
+        .header()
+        .external_refs
+        .iter()
+        .map(|external| external.folder.as_deref().unwrap())
+        .collect();
+    assert_eq!(folders, ["Outer", "Outer/Inner"]);
+    assert_eq!(streamed.body().unwrap(), body);
+    assert_eq!(read.body().unwrap(), body);
+}
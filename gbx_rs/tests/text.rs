@@ -0,0 +1,42 @@
+//! Files in the text format, where every value is a token
+
+mod common;
+
+use common::test_data;
+use gbx_rs::{parse::CGame, ByteFormat, GbxErrorInner, Node};
+
+const TEXT_GHOST: &str = "Text.Ghost.Gbx";
+
+#[test]
+fn text_ghost() {
+    let data = test_data(TEXT_GHOST);
+    let node = Node::read_from(&data).unwrap();
+    assert_eq!(node.byte_format(), ByteFormat::Text);
+    let CGame::CtnGhost(ghost) = node.parse().unwrap() else {
+        panic!("not a ghost");
+    };
+
+    assert_eq!(ghost.race_time, Some(54321));
+    assert_eq!(ghost.respawns, Some(2));
+    assert_eq!(ghost.light_trail_color, Some([1.0, 0.5, 0.0]));
+    let times: Vec<_> = ghost.checkpoints.iter().map(|cp| cp.time).collect();
+    assert_eq!(times, [Some(20000), Some(54321)]);
+    assert_eq!(ghost.login.as_deref(), Some("player"));
+    let inputs: Vec<_> = ghost
+        .inputs
+        .iter()
+        .map(|input| (input.time, &*input.name, input.data))
+        .collect();
+    assert_eq!(inputs, [(0, "Accel", 1), (1000, "Steer", 65536)]);
+    assert_eq!(ghost.game_version.as_deref(), Some("3.3.0"));
+}
+
+#[test]
+fn out_of_range_text_integers_fail() {
+    // the first input's control name index is a u8
+    let data = String::from_utf8(test_data(TEXT_GHOST)).unwrap();
+    let data = data.replace("\r\n0 0 1\r\n", "\r\n0 256 1\r\n");
+    let node = Node::read_from(&data).unwrap();
+    let err = node.parse().unwrap_err();
+    assert!(matches!(*err, GbxErrorInner::InvalidText { .. }), "{}", err);
+}
@@ -0,0 +1 @@
+ALTER TABLE map_data DROP COLUMN parse_report;
@@ -0,0 +1 @@
+ALTER TABLE map_data ADD COLUMN parse_report TEXT;
@@ -0,0 +1 @@
+ALTER TABLE map_data DROP COLUMN parsed_map;
@@ -0,0 +1 @@
+ALTER TABLE map_data ADD COLUMN parsed_map TEXT;
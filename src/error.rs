@@ -142,8 +142,9 @@ pub enum ApiErrorInner {
     InvalidGbx {
         #[serde(skip)]
         #[ts(skip)]
-        #[from]
         error: gbx_rs::GbxError,
+        /// Where in the file parsing failed, so it can be reproduced from the file
+        location: Option<String>,
     },
 
     #[error("Not a map")]
@@ -171,6 +172,14 @@ pub enum ApiErrorInner {
         error: std::io::Error,
     },
 
+    #[error("Background task failed: {error}")]
+    Task {
+        #[serde(skip)]
+        #[ts(skip)]
+        #[from]
+        error: tokio::task::JoinError,
+    },
+
     #[error("No such tag: {tag}")]
     NoSuchTag { tag: String },
 
@@ -245,6 +254,12 @@ pub enum ApiErrorInner {
     #[error("Invalid map: {error}")]
     InvalidMap { error: &'static str },
 
+    #[error("XML header does not match map data: {}", mismatches.join("; "))]
+    XmlMismatch {
+        /// Each field that differs, with its value in the XML header and in the map
+        mismatches: Vec<String>,
+    },
+
     #[error("Missing author for map {map_id}")]
     MissingAuthor { map_id: i32 },
 
@@ -264,6 +279,15 @@ pub enum ApiErrorInner {
     CannotModifySelfPermissions,
 }
 
+impl From<gbx_rs::GbxError> for ApiErrorInner {
+    fn from(error: gbx_rs::GbxError) -> Self {
+        ApiErrorInner::InvalidGbx {
+            location: error.location().map(ToString::to_string),
+            error,
+        }
+    }
+}
+
 impl From<(axum::http::StatusCode, &'static str)> for ApiErrorInner {
     fn from(value: (axum::http::StatusCode, &'static str)) -> Self {
         ApiErrorInner::Rejected { error: value }
@@ -302,6 +326,7 @@ impl IntoResponse for ApiError {
             | ApiErrorInner::UnexpectedResponse { .. }
             | ApiErrorInner::Tera { .. }
             | ApiErrorInner::StdIo { .. }
+            | ApiErrorInner::Task { .. }
             | ApiErrorInner::MissingAuthor { .. }
             | ApiErrorInner::MissingUploader { .. }
             | ApiErrorInner::Time { .. } => StatusCode::INTERNAL_SERVER_ERROR,
@@ -328,6 +353,7 @@ impl IntoResponse for ApiError {
             | ApiErrorInner::TimeRange { .. }
             | ApiErrorInner::LastModifiedTimeTooLarge
             | ApiErrorInner::InvalidMap { .. }
+            | ApiErrorInner::XmlMismatch { .. }
             | ApiErrorInner::CannotUsurpAuthor
             | ApiErrorInner::NotUuid { .. } => StatusCode::BAD_REQUEST,
 
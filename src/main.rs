@@ -124,6 +124,10 @@ async fn main() -> anyhow::Result<()> {
                 &CONFIG.route_api_v1("/map/{map_id}/thumbnail/{size}"),
                 get(routes::api::map_thumbnail_size).layer(long_cache),
             )
+            .route(
+                &CONFIG.route_api_v1("/map/{map_id}/parsed"),
+                get(routes::api::map_parsed),
+            )
             .route(
                 &CONFIG.route_api_v1("/map/{map_id}/manage"),
                 post(routes::api::map_manage),
@@ -103,6 +103,19 @@ pub struct MapTag {
 pub struct MapData {
     pub ap_map_id: i32,
     pub gbx_data: Vec<u8>,
+    /// JSON [`gbx_rs::ParseReport`] from when the map was uploaded
+    pub parse_report: Option<String>,
+    /// JSON [`gbx_rs::parse::CtnChallenge`] from when the map was uploaded
+    pub parsed_map: Option<String>,
+}
+
+#[derive(Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = map_data)]
+#[diesel(primary_key(ap_map_id))]
+#[diesel(belongs_to(Map, foreign_key = ap_map_id))]
+pub struct MapParsed {
+    pub ap_map_id: i32,
+    pub parsed_map: Option<String>,
 }
 
 #[derive(Insertable)]
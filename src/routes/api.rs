@@ -158,6 +158,67 @@ async fn map_thumbnail_inner(state: AppState, path: ThumbnailPath) -> Result<Res
     Ok(([(header::CONTENT_TYPE, "image/webp")], thumbnail_data).into_response())
 }
 
+/// Fields of a parsed map left out of its JSON: the password, and binary data that's too large,
+/// like the thumbnail, which has its own route
+const OMITTED_MAP_FIELDS: [&str; 3] = ["password", "embedded_zip", "thumbnail_data"];
+
+/// A parsed map as JSON, without [`OMITTED_MAP_FIELDS`]
+fn parsed_map_json(mut map: serde_json::Value) -> Result<String, ApiError> {
+    if let Some(fields) = map.as_object_mut() {
+        for field in OMITTED_MAP_FIELDS {
+            fields.remove(field);
+        }
+    }
+    serde_json::to_string(&map).context("Serializing parsed map")
+}
+
+/// The map as parsed when it was uploaded, as JSON. Maps uploaded before that was kept are
+/// parsed again.
+pub async fn map_parsed(
+    State(state): State<AppState>,
+    WithRejection(Path(map_id), _): WithRejection<Path<i32>, ApiError>,
+) -> Result<Response, ApiError> {
+    let mut conn = state.db.get().await?;
+
+    let Some(map) = crate::schema::map::dsl::map
+        .select(crate::models::Map::as_select())
+        .find(map_id)
+        .get_result(&mut conn)
+        .await
+        .optional()?
+    else {
+        return Err(ApiErrorInner::MapNotFound { map_id }.into());
+    };
+
+    let map_parsed = crate::models::MapParsed::belonging_to(&map)
+        .select(crate::models::MapParsed::as_select())
+        .get_result(&mut conn)
+        .await?;
+
+    let parsed_map = match map_parsed.parsed_map {
+        // snapshots stored before some fields were left out still have them
+        Some(parsed_map) => parsed_map_json(
+            serde_json::from_str(&parsed_map).context("Reading stored parsed map")?,
+        )?,
+        None => {
+            let gbx_data: Vec<u8> = crate::models::MapData::belonging_to(&map)
+                .select(crate::schema::map_data::dsl::gbx_data)
+                .get_result(&mut conn)
+                .await?;
+            let node = gbx_rs::Node::read_from_with_limits(&gbx_data, map_limits())
+                .context("Reading stored map")?;
+            let gbx_rs::parse::CGame::CtnChallenge(map) =
+                node.parse().context("Parsing stored map")?
+            else {
+                return Err(ApiErrorInner::NotAMap.into());
+            };
+            parsed_map_json(serde_json::to_value(&map).context("Serializing parsed map")?)?
+        }
+    };
+
+    Ok(([(header::CONTENT_TYPE, "application/json")], parsed_map).into_response())
+}
+
 #[derive(Serialize, TS)]
 #[ts(export)]
 #[serde(tag = "type")]
@@ -174,6 +235,86 @@ struct MapUploadMeta {
     last_modified: f64,
 }
 
+/// Uploads are at most 20 MB, and their bodies don't decompress to much more than that
+fn map_limits() -> gbx_rs::ParseLimits {
+    let mut limits = gbx_rs::ParseLimits::default();
+    limits.max_decompressed_size = 64 << 20;
+    limits
+}
+
+/// Reads the chunks of an upload as they arrive, keeping a copy of all of it
+struct UploadReader {
+    receiver: tokio::sync::mpsc::Receiver<axum::body::Bytes>,
+    chunk: axum::body::Bytes,
+    data: Vec<u8>,
+}
+
+impl std::io::Read for UploadReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.chunk.is_empty() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => self.chunk = chunk,
+                // the upload ended
+                None => return Ok(0),
+            }
+        }
+
+        let count = buf.len().min(self.chunk.len());
+        buf[..count].copy_from_slice(&self.chunk[..count]);
+        self.data.extend_from_slice(&self.chunk[..count]);
+        self.chunk = self.chunk.slice(count..);
+        Ok(count)
+    }
+}
+
+/// Chunks of an upload that can arrive before the map reader catches up. This only bounds the
+/// chunks waiting to be read, as the reader keeps a copy of the whole file.
+const UPLOAD_CHUNKS_IN_FLIGHT: usize = 4;
+
+/// Reads a map from a multipart field while it is still arriving, returning it along with the
+/// data of the whole file. Errors reading the map are returned inside, and the rest of the field
+/// is still read and discarded, so the other fields of the upload can be read after it.
+async fn stream_map(
+    mut field: axum::extract::multipart::Field<'_>,
+) -> Result<(Result<gbx_rs::Node<'static>, gbx_rs::GbxError>, Vec<u8>), ApiError> {
+    // bounded, so an upload arriving faster than it is parsed waits instead of piling up in the
+    // channel. The whole file is still kept in memory, to be stored.
+    let (sender, receiver) = tokio::sync::mpsc::channel(UPLOAD_CHUNKS_IN_FLIGHT);
+    let reader = tokio::task::spawn_blocking(move || {
+        let mut reader = UploadReader {
+            receiver,
+            chunk: Default::default(),
+            data: Vec::new(),
+        };
+        let node =
+            gbx_rs::Node::read_stream_with_limits(&mut reader, map_limits()).and_then(|node| {
+                // anything after the body is still part of the file that gets stored
+                std::io::copy(&mut reader, &mut std::io::sink())
+                    .map_err(gbx_rs::GbxErrorInner::from)?;
+                Ok(node)
+            });
+        (node, reader.data)
+    });
+
+    let mut sender = Some(sender);
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .context("Reading value of multipart field map_data")?
+    {
+        let Some(chunk_sender) = &sender else {
+            continue;
+        };
+        if chunk_sender.send(chunk).await.is_err() {
+            // the map couldn't be read, which the reader returns
+            sender = None;
+        }
+    }
+    drop(sender);
+
+    reader.await.context("Reading uploaded map")
+}
+
 pub async fn map_upload(
     State(state): State<AppState>,
     auth: NadeoAuthSession,
@@ -194,6 +335,11 @@ pub async fn map_upload(
             .into());
         };
         let name = String::from(name);
+        if &name == "map_data" {
+            map_data = Some(stream_map(field).await?);
+            continue;
+        }
+
         let data = field
             .bytes()
             .await
@@ -203,12 +349,10 @@ pub async fn map_upload(
 
         if &name == "map_meta" {
             map_meta = Some(data);
-        } else if &name == "map_data" {
-            map_data = Some(data);
         }
     }
 
-    let Some(map_data) = map_data else {
+    let Some((map_node, map_data)) = map_data else {
         return Err(ApiErrorInner::MissingFromMultipart {
             error: "Value of map_data",
         }
@@ -254,12 +398,16 @@ pub async fn map_upload(
         return Err(ApiErrorInner::NotAMap.into());
     }
 
-    let map_node = gbx_rs::Node::read_from(&map_data).context("Parsing map for upload")?;
-    let gbx_rs::parse::CGame::CtnChallenge(map) =
-        map_node.parse().context("Parsing full map data")?
+    let map_node = map_node.context("Parsing map for upload")?;
+    let (gbx_rs::parse::CGame::CtnChallenge(map), parse_report) = map_node
+        .parse_with_report()
+        .context("Parsing full map data")?
     else {
         return Err(ApiErrorInner::NotAMap.into());
     };
+    let parse_report = serde_json::to_string(&parse_report).context("Serializing parse report")?;
+    let parsed_map =
+        parsed_map_json(serde_json::to_value(&map).context("Serializing parsed map")?)?;
 
     let Some(map_info) = map.map_info.as_ref() else {
         return Err(ApiErrorInner::InvalidMap {
@@ -269,7 +417,17 @@ pub async fn map_upload(
     };
 
     let author_account_id =
-        nadeo::login_to_account_id(map_info.author).context("Parsing map author")?;
+        nadeo::login_to_account_id(&map_info.author).context("Parsing map author")?;
+
+    if let Some(header_xml) = map.header_xml() {
+        let mismatches = header_xml.mismatches(&map);
+        if !mismatches.is_empty() {
+            return Err(ApiErrorInner::XmlMismatch {
+                mismatches: mismatches.iter().map(ToString::to_string).collect(),
+            }
+            .into());
+        }
+    }
 
     let Some(map_name) = map.map_name else {
         return Err(ApiErrorInner::InvalidMap {
@@ -302,6 +460,22 @@ pub async fn map_upload(
         return Err(ApiErrorInner::NotValidated.into());
     };
 
+    if let Some(ghost_time) = map
+        .challenge_parameters
+        .as_ref()
+        .and_then(gbx_rs::NodeRef::local)
+        .and_then(|params| params.validation_ghost.as_ref())
+        .and_then(gbx_rs::NodeRef::local)
+        .and_then(|ghost| ghost.race_time)
+    {
+        if ghost_time != author_time {
+            return Err(ApiErrorInner::InvalidMap {
+                error: "Validation ghost time does not match author time",
+            }
+            .into());
+        }
+    }
+
     let ap_uploader_id = auth.user_id();
     let ap_author_id = if auth.account_id() == author_account_id {
         ap_uploader_id
@@ -377,8 +551,6 @@ pub async fn map_upload(
         )
         .context("Re-encoding small thumbnail")?;
 
-    let map_buffer = map_data.to_vec();
-
     let new_map = conn
         .transaction(|conn| {
             async move {
@@ -403,7 +575,7 @@ pub async fn map_upload(
                     Err(err) => {
                         if let Some(exists) = crate::schema::map::dsl::map
                             .select(crate::models::Map::as_select())
-                            .filter(crate::schema::map::dsl::gbx_mapuid.eq(map_info.id))
+                            .filter(crate::schema::map::dsl::gbx_mapuid.eq(&*map_info.id))
                             .get_result(conn)
                             .await
                             .optional()?
@@ -421,7 +593,9 @@ pub async fn map_upload(
                 diesel::insert_into(crate::schema::map_data::table)
                     .values(crate::models::MapData {
                         ap_map_id: new_map.ap_map_id,
-                        gbx_data: map_buffer,
+                        gbx_data: map_data,
+                        parse_report: Some(parse_report),
+                        parsed_map: Some(parsed_map),
                     })
                     .execute(conn)
                     .await?;
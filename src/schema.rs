@@ -32,6 +32,8 @@ diesel::table! {
     map_data (ap_map_id) {
         ap_map_id -> Int4,
         gbx_data -> Bytea,
+        parse_report -> Nullable<Text>,
+        parsed_map -> Nullable<Text>,
     }
 }
 